@@ -0,0 +1,113 @@
+use std::time::{Duration, Instant};
+
+use space_invaders_rust::collision::{self, Broadphase};
+use space_invaders_rust::game::{Game, GameObject, PIXEL_SIZE};
+use space_invaders_rust::invader::Invader;
+use space_invaders_rust::player::Player;
+use space_invaders_rust::settings::{CollisionMode, Settings};
+
+const TICKS: u32 = 50;
+
+// A formation of `columns` by `rows` invaders squeezed into the top half of
+// the field, with a bullet under every other column and a shot over the rest.
+fn formation(columns: u32, rows: u32, mode: CollisionMode) -> (Player, Game) {
+    let settings = Settings {
+        collision: mode,
+        ..Settings::default()
+    };
+
+    let mut game = Game::new(0, settings);
+    let mut player = Player::new(&game.layout);
+
+    let step_x = (game.layout.width - 12 * PIXEL_SIZE) as f32 / columns as f32;
+    let step_y = (game.layout.height / 2) as f32 / rows as f32;
+
+    game.invaders = (0..rows * columns)
+        .map(|i| {
+            Invader::new(
+                (i % columns) as f32 * step_x,
+                (i / columns) as f32 * step_y,
+                12 * PIXEL_SIZE,
+                8 * PIXEL_SIZE,
+                String::from("invader_texture1"),
+                i / columns,
+                i % columns,
+            )
+        })
+        .collect();
+
+    let bottom = rows as f32 * step_y;
+
+    for column in 0..columns {
+        let x = column as f32 * step_x + 6.0 * PIXEL_SIZE as f32;
+
+        if column % 2 == 0 {
+            player.bullets.push(GameObject::new(
+                x,
+                bottom,
+                PIXEL_SIZE as u32,
+                4 * PIXEL_SIZE as u32,
+                String::from("shot_texture"),
+            ));
+        } else {
+            game.invader_shots.push(GameObject::new(
+                x,
+                bottom + 20.0 * PIXEL_SIZE as f32,
+                3 * PIXEL_SIZE as u32,
+                7 * PIXEL_SIZE as u32,
+                String::from("invader_shot_texture"),
+            ));
+        }
+    }
+
+    (player, game)
+}
+
+// Average time of one collision pass, leaving out the time to set up each
+// fresh formation.
+fn measure(
+    columns: u32,
+    rows: u32,
+    mode: CollisionMode,
+    mut update: impl FnMut(&mut Player, &mut Game),
+) -> Duration {
+    let mut total = Duration::ZERO;
+
+    for _ in 0..TICKS {
+        let (mut player, mut game) = formation(columns, rows, mode);
+
+        let start = Instant::now();
+        update(&mut player, &mut game);
+        total += start.elapsed();
+    }
+
+    total / TICKS
+}
+
+fn main() {
+    println!(
+        "{:>7} {:>8} {:>12} {:>12} {:>8}",
+        "mode", "invaders", "brute force", "broadphase", "speedup"
+    );
+
+    for mode in [CollisionMode::Boxes, CollisionMode::Pixels] {
+        for (columns, rows) in [(11, 5), (22, 10), (44, 20), (88, 40)] {
+            let (_, game) = formation(columns, rows, mode);
+            let mut broadphase = Broadphase::new(&game.layout);
+
+            let brute_force = measure(columns, rows, mode, collision::update_brute_force);
+            let grid = measure(columns, rows, mode, |player, game| {
+                collision::update(&mut broadphase, player, game)
+            });
+
+            println!(
+                "{:>7} {:>8} {:>12.2?} {:>12.2?} {:>7.1}x",
+                mode.name(),
+                columns * rows,
+                brute_force,
+                grid,
+                brute_force.as_secs_f64() / grid.as_secs_f64()
+            );
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas, Texture, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+
+use space_invaders_rust::barrier::{Barrier, BARRIER_HEIGHT, BARRIER_WIDTH};
+use space_invaders_rust::layout::BARRIER_COUNT;
+
+const ATLAS_WIDTH: u32 = 1024;
+// Keeps filtering from bleeding one sprite into its neighbour when scaled.
+const PADDING: u32 = 1;
+const MISSING: &str = "missing_texture";
+const BARRIER: &str = "barrier_texture";
+
+// Every sprite packed into a single texture, so drawing a frame only ever
+// binds one texture.
+pub struct Atlas<'a> {
+    pub texture: Texture<'a>,
+    regions: HashMap<String, Rect>,
+    // Each barrier gets its own region, redrawn from the untouched sprite
+    // whenever its pixels change.
+    barrier_texture: Texture<'a>,
+    barrier_slots: [Option<[[u32; BARRIER_WIDTH]; BARRIER_HEIGHT]>; BARRIER_COUNT],
+}
+
+pub fn barrier_slot(index: usize) -> String {
+    format!("{}_{}", BARRIER, index)
+}
+
+// The part of a barrier region covered by one of its cells.
+fn barrier_cell(region: Rect, col: usize, row: usize) -> Option<Rect> {
+    let x = |col: usize| region.x() + (col as u32 * region.width() / BARRIER_WIDTH as u32) as i32;
+    let y = |row: usize| region.y() + (row as u32 * region.height() / BARRIER_HEIGHT as u32) as i32;
+
+    let (left, right) = (x(col), x(col + 1));
+    let (top, bottom) = (y(row), y(row + 1));
+
+    if right == left || bottom == top {
+        return None;
+    }
+
+    Some(Rect::new(
+        left,
+        top,
+        (right - left) as u32,
+        (bottom - top) as u32,
+    ))
+}
+
+// Places the rectangles in rows, tallest first, and returns each position
+// along with the total height used.
+fn pack(sizes: &[(String, u32, u32)], width: u32) -> (HashMap<String, Rect>, u32) {
+    let mut order: Vec<&(String, u32, u32)> = sizes.iter().collect();
+    order.sort_by(|a, b| b.2.cmp(&a.2).then(b.1.cmp(&a.1)).then(a.0.cmp(&b.0)));
+
+    let mut regions = HashMap::new();
+    let (mut x, mut y, mut row_height) = (0, 0, 0);
+
+    for (name, w, h) in order {
+        if x > 0 && x + w > width {
+            x = 0;
+            y += row_height + PADDING;
+            row_height = 0;
+        }
+
+        regions.insert(name.clone(), Rect::new(x as i32, y as i32, *w, *h));
+
+        x += w + PADDING;
+        row_height = row_height.max(*h);
+    }
+
+    (regions, y + row_height)
+}
+
+impl<'a> Atlas<'a> {
+    pub fn new(
+        canvas: &mut Canvas<Window>,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        mut textures: HashMap<String, Texture<'a>>,
+        mut missing_texture: Texture<'a>,
+    ) -> Result<Atlas<'a>, String> {
+        let mut barrier_texture = textures
+            .remove(BARRIER)
+            .ok_or_else(|| format!("no '{}' sprite", BARRIER))?;
+        barrier_texture.set_blend_mode(BlendMode::None);
+
+        let mut sprites: Vec<(&str, &mut Texture<'a>)> = textures
+            .iter_mut()
+            .map(|(name, texture)| (name.as_str(), texture))
+            .collect();
+        sprites.push((MISSING, &mut missing_texture));
+
+        let mut sizes: Vec<(String, u32, u32)> = sprites
+            .iter()
+            .map(|(name, texture)| {
+                let query = texture.query();
+                (String::from(*name), query.width, query.height)
+            })
+            .collect();
+
+        let barrier_query = barrier_texture.query();
+
+        for index in 0..BARRIER_COUNT {
+            sizes.push((
+                barrier_slot(index),
+                barrier_query.width,
+                barrier_query.height,
+            ));
+        }
+
+        let width = sizes
+            .iter()
+            .map(|(_, width, _)| *width)
+            .max()
+            .unwrap_or(1)
+            .max(ATLAS_WIDTH);
+        let (regions, height) = pack(&sizes, width);
+
+        let mut texture = texture_creator
+            .create_texture_target(PixelFormatEnum::ARGB8888, width, height.max(1))
+            .map_err(|e| e.to_string())?;
+
+        // Copy each sprite's alpha as is rather than blending it onto the
+        // cleared atlas.
+        for (_, sprite) in sprites.iter_mut() {
+            sprite.set_blend_mode(BlendMode::None);
+        }
+
+        let mut result = Ok(());
+
+        canvas
+            .with_texture_canvas(&mut texture, |texture_canvas| {
+                texture_canvas.set_draw_color(Color::RGBA(0, 0, 0, 0));
+                texture_canvas.clear();
+
+                for (name, sprite) in &sprites {
+                    if let Err(err) = texture_canvas.copy(sprite, None, regions[*name]) {
+                        result = Err(err);
+                    }
+                }
+            })
+            .map_err(|e| e.to_string())?;
+
+        result?;
+
+        texture.set_blend_mode(BlendMode::Blend);
+
+        Ok(Atlas {
+            texture,
+            regions,
+            barrier_texture,
+            barrier_slots: [None; BARRIER_COUNT],
+        })
+    }
+
+    // Brings each barrier's region up to date with its pixels, clearing the
+    // parts that have been shot away.
+    pub fn sync_barriers(
+        &mut self,
+        canvas: &mut Canvas<Window>,
+        barriers: &[Barrier],
+    ) -> Result<(), String> {
+        for (index, (barrier, slot)) in barriers
+            .iter()
+            .zip(self.barrier_slots.iter_mut())
+            .enumerate()
+        {
+            if *slot == Some(barrier.pixels) {
+                continue;
+            }
+
+            let region = self.regions[&barrier_slot(index)];
+            let source = &self.barrier_texture;
+            let mut result = Ok(());
+
+            canvas
+                .with_texture_canvas(&mut self.texture, |texture_canvas| {
+                    if let Err(err) = texture_canvas.copy(source, None, region) {
+                        result = Err(err);
+                    }
+
+                    texture_canvas.set_draw_color(Color::RGBA(0, 0, 0, 0));
+
+                    for (row, cells) in barrier.pixels.iter().enumerate() {
+                        for (col, value) in cells.iter().enumerate() {
+                            if *value != 0 {
+                                continue;
+                            }
+
+                            if let Some(cell) = barrier_cell(region, col, row) {
+                                if let Err(err) = texture_canvas.fill_rect(cell) {
+                                    result = Err(err);
+                                }
+                            }
+                        }
+                    }
+                })
+                .map_err(|e| e.to_string())?;
+
+            result?;
+            *slot = Some(barrier.pixels);
+        }
+
+        Ok(())
+    }
+
+    pub fn region(&self, name: &str) -> Rect {
+        match self.regions.get(name) {
+            Some(rect) => *rect,
+            None => self.regions[MISSING],
+        }
+    }
+}
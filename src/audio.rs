@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+
+use sdl2::mixer::{self, Channel, Chunk, DEFAULT_FORMAT, MAX_VOLUME};
+use sdl2::AudioSubsystem;
+
+use space_invaders_rust::game::{Game, GameEvent, State};
+
+use crate::synth::{self, Envelope, Voice, Waveform};
+
+const FREQUENCY: i32 = 44_100;
+const CHUNK_SIZE: i32 = 1_024;
+
+const MARCH_FREQS: [f32; 4] = [98.0, 87.3, 77.8, 73.4];
+
+const PERCUSSIVE: Envelope = Envelope {
+    attack: 0.005,
+    decay: 0.08,
+    sustain: 0.4,
+    release: 0.08,
+};
+
+const SUSTAINED: Envelope = Envelope {
+    attack: 0.0,
+    decay: 0.0,
+    sustain: 1.0,
+    release: 0.0,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sound {
+    PlayerShot,
+    March1,
+    March2,
+    March3,
+    March4,
+    InvaderKilled,
+    PlayerKilled,
+    Ufo,
+    UfoHit,
+}
+
+const SOUNDS: [(Sound, i32); 9] = [
+    (Sound::PlayerShot, MAX_VOLUME / 2),
+    (Sound::March1, MAX_VOLUME),
+    (Sound::March2, MAX_VOLUME),
+    (Sound::March3, MAX_VOLUME),
+    (Sound::March4, MAX_VOLUME),
+    (Sound::InvaderKilled, MAX_VOLUME),
+    (Sound::PlayerKilled, MAX_VOLUME),
+    (Sound::Ufo, MAX_VOLUME / 2),
+    (Sound::UfoHit, MAX_VOLUME),
+];
+
+const MARCH: [Sound; 4] = [Sound::March1, Sound::March2, Sound::March3, Sound::March4];
+
+impl Sound {
+    fn channel(&self) -> Channel {
+        Channel(*self as i32)
+    }
+
+    fn voices(&self) -> Vec<Voice> {
+        match self {
+            Sound::PlayerShot => vec![
+                Voice {
+                    waveform: Waveform::Square,
+                    start_freq: 1400.0,
+                    end_freq: 300.0,
+                    duration: 0.18,
+                    envelope: PERCUSSIVE,
+                    volume: 0.4,
+                    vibrato: None,
+                },
+                Voice {
+                    waveform: Waveform::Noise,
+                    start_freq: 4000.0,
+                    end_freq: 1000.0,
+                    duration: 0.1,
+                    envelope: PERCUSSIVE,
+                    volume: 0.2,
+                    vibrato: None,
+                },
+            ],
+            Sound::March1 | Sound::March2 | Sound::March3 | Sound::March4 => {
+                let freq = MARCH_FREQS[*self as usize - Sound::March1 as usize];
+
+                vec![Voice {
+                    waveform: Waveform::Square,
+                    start_freq: freq,
+                    end_freq: freq,
+                    duration: 0.09,
+                    envelope: PERCUSSIVE,
+                    volume: 0.6,
+                    vibrato: None,
+                }]
+            }
+            Sound::InvaderKilled => vec![
+                Voice {
+                    waveform: Waveform::Noise,
+                    start_freq: 3000.0,
+                    end_freq: 300.0,
+                    duration: 0.3,
+                    envelope: PERCUSSIVE,
+                    volume: 0.6,
+                    vibrato: None,
+                },
+                Voice {
+                    waveform: Waveform::Square,
+                    start_freq: 400.0,
+                    end_freq: 100.0,
+                    duration: 0.2,
+                    envelope: PERCUSSIVE,
+                    volume: 0.3,
+                    vibrato: None,
+                },
+            ],
+            Sound::PlayerKilled => vec![Voice {
+                waveform: Waveform::Noise,
+                start_freq: 2000.0,
+                end_freq: 100.0,
+                duration: 1.0,
+                envelope: Envelope {
+                    attack: 0.01,
+                    decay: 0.3,
+                    sustain: 0.4,
+                    release: 0.6,
+                },
+                volume: 0.8,
+                vibrato: None,
+            }],
+            // Two full vibrato cycles so the chunk loops without a seam.
+            Sound::Ufo => vec![Voice {
+                waveform: Waveform::Square,
+                start_freq: 700.0,
+                end_freq: 700.0,
+                duration: 0.25,
+                envelope: SUSTAINED,
+                volume: 0.4,
+                vibrato: Some((8.0, 0.25)),
+            }],
+            Sound::UfoHit => vec![Voice {
+                waveform: Waveform::Square,
+                start_freq: 1200.0,
+                end_freq: 200.0,
+                duration: 0.6,
+                envelope: PERCUSSIVE,
+                volume: 0.5,
+                vibrato: Some((16.0, 0.3)),
+            }],
+        }
+    }
+}
+
+// Without a sound device the audio is silent: there are no chunks to play
+// and nothing is sent to the mixer.
+pub struct Audio {
+    audio_subsystem: Option<AudioSubsystem>,
+    chunks: HashMap<Sound, Chunk>,
+    volumes: HashMap<Sound, i32>,
+    muted: bool,
+}
+
+impl Audio {
+    pub fn new(audio_subsystem: Result<AudioSubsystem, String>) -> Audio {
+        match audio_subsystem.and_then(Audio::open) {
+            Ok(audio) => audio,
+            Err(err) => {
+                println!("Sound disabled: {}", err);
+                Audio::silent()
+            }
+        }
+    }
+
+    fn silent() -> Audio {
+        Audio {
+            audio_subsystem: None,
+            chunks: HashMap::new(),
+            volumes: HashMap::new(),
+            muted: false,
+        }
+    }
+
+    fn open(audio_subsystem: AudioSubsystem) -> Result<Audio, String> {
+        mixer::open_audio(FREQUENCY, DEFAULT_FORMAT, 2, CHUNK_SIZE)?;
+
+        // From here on dropping the audio closes the mixer again.
+        let mut audio = Audio::silent();
+        audio.audio_subsystem = Some(audio_subsystem);
+
+        mixer::allocate_channels(SOUNDS.len() as i32);
+
+        // The device may not have granted the format asked for, so the
+        // sounds are encoded in the one it actually uses.
+        let (frequency, format, channels) = mixer::query_spec()?;
+
+        for (sound, volume) in SOUNDS {
+            let samples = synth::render(&sound.voices(), frequency as u32);
+            let pcm = synth::to_pcm(&samples, channels as usize, format)?;
+            let chunk = Chunk::from_raw_buffer(pcm)?;
+
+            audio.chunks.insert(sound, chunk);
+            audio.set_volume(sound, volume);
+        }
+
+        Ok(audio)
+    }
+
+    fn is_open(&self) -> bool {
+        self.audio_subsystem.is_some()
+    }
+
+    pub fn play(&self, sound: Sound) {
+        self.play_looped(sound, 0);
+    }
+
+    fn play_looped(&self, sound: Sound, loops: i32) {
+        if self.muted {
+            return;
+        }
+
+        if let Some(chunk) = self.chunks.get(&sound) {
+            if let Err(err) = sound.channel().play(chunk, loops) {
+                println!("{}", err);
+            }
+        }
+    }
+
+    pub fn set_volume(&mut self, sound: Sound, volume: i32) {
+        let volume = volume.clamp(0, MAX_VOLUME);
+        self.volumes.insert(sound, volume);
+
+        if self.is_open() && !self.muted {
+            sound.channel().set_volume(volume);
+        }
+    }
+
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+
+        for (sound, volume) in &self.volumes {
+            sound.channel().set_volume(if muted { 0 } else { *volume });
+        }
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.set_muted(!self.muted);
+    }
+
+    pub fn update(&self, game: &Game) {
+        if !self.is_open() {
+            return;
+        }
+
+        for event in &game.events {
+            match event {
+                GameEvent::PlayerShot => self.play(Sound::PlayerShot),
+                GameEvent::InvaderMarch(note) => self.play(MARCH[*note as usize % MARCH.len()]),
+                GameEvent::InvaderKilled => self.play(Sound::InvaderKilled),
+                GameEvent::PlayerKilled => self.play(Sound::PlayerKilled),
+                GameEvent::UfoKilled => {
+                    Sound::Ufo.channel().halt();
+                    self.play(Sound::UfoHit);
+                }
+            }
+        }
+
+        let ufo_channel = Sound::Ufo.channel();
+
+        if game.ufo_active && game.state == State::Playing {
+            if !ufo_channel.is_playing() {
+                self.play_looped(Sound::Ufo, -1);
+            }
+        } else if ufo_channel.is_playing() {
+            ufo_channel.halt();
+        }
+    }
+}
+
+impl Drop for Audio {
+    fn drop(&mut self) {
+        self.chunks.clear();
+
+        if self.is_open() {
+            mixer::close_audio();
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use crate::input::Input;
+use crate::simulation::Simulation;
+
+const AIM_TOLERANCE: f32 = 12.0;
+const DANGER_HEIGHT: f32 = 240.0;
+
+pub fn autopilot(sim: &Simulation) -> Input {
+    let player = &sim.player.game_object.rect;
+    let center = player.x + player.width() / 2.0;
+
+    let danger = sim.game.invader_shots.iter().find(|shot| {
+        shot.rect.x + shot.rect.width() > player.x - AIM_TOLERANCE
+            && shot.rect.x < player.x + player.width() + AIM_TOLERANCE
+            && shot.rect.y > player.y - DANGER_HEIGHT
+    });
+
+    if let Some(shot) = danger {
+        let dodge_left = shot.rect.x > center;
+
+        return Input {
+            left: dodge_left,
+            right: !dodge_left,
+            fire: false,
+        };
+    }
+
+    let target = sim
+        .game
+        .invaders
+        .iter()
+        .map(|invader| invader.game_object.rect.x + invader.game_object.rect.width() / 2.0)
+        .min_by(|a, b| (a - center).abs().total_cmp(&(b - center).abs()));
+
+    match target {
+        Some(x) => Input {
+            left: x < center - AIM_TOLERANCE,
+            right: x > center + AIM_TOLERANCE,
+            fire: (x - center).abs() <= AIM_TOLERANCE && sim.timer.time % 2 == 0,
+        },
+        None => Input::default(),
+    }
+}
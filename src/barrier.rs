@@ -1,91 +1,220 @@
-use sdl2::rect::FRect;
+use std::ops::Range;
 
-use crate::game::{GameObject, CANVAS_HEIGHT, HEIGHT_DIV_4, PIXEL_SIZE};
+use crate::rect::FRect;
+
+use crate::game::{GameObject, PIXEL_SIZE};
 use crate::texture_templates::BARRIER;
 
-#[derive(Debug)]
-pub struct Collider {
-    pub rect: FRect,
-    pub is_destroyed: bool,
-}
+pub const BARRIER_WIDTH: usize = BARRIER[0].len();
+pub const BARRIER_HEIGHT: usize = BARRIER.len();
 
 pub struct Barrier {
     pub game_object: GameObject,
-    pub colliders: Vec<Collider>,
+    // One cell per sprite pixel; shots carve cells out as they hit.
+    pub pixels: [[u32; BARRIER_WIDTH]; BARRIER_HEIGHT],
 }
 
 impl Barrier {
-    pub fn new(x: f32) -> Barrier {
-        let y = CANVAS_HEIGHT - HEIGHT_DIV_4;
-        let width = 24 * PIXEL_SIZE as u32;
-        let height = 18 * PIXEL_SIZE as u32;
-
-        let game_object =
-            GameObject::new(x, y as f32, width, height, String::from("barrier_texture"));
-        let colliders = Collider::get_colliders(&game_object);
+    pub fn new(x: f32, y: f32) -> Barrier {
+        let width = BARRIER_WIDTH as u32 * PIXEL_SIZE as u32;
+        let height = BARRIER_HEIGHT as u32 * PIXEL_SIZE as u32;
 
         Barrier {
-            game_object,
-            colliders,
+            game_object: GameObject::new(x, y, width, height, String::from("barrier_texture")),
+            pixels: BARRIER,
         }
     }
-}
 
-impl Collider {
-    pub fn new(x: f32, y: f32, height: u32) -> Collider {
-        Collider {
-            rect: FRect::new(x, y, PIXEL_SIZE as f32, height as f32 * PIXEL_SIZE as f32),
-            is_destroyed: false,
+    // The columns and rows of cells a rectangle covers, if any.
+    fn cells(&self, rect: &FRect) -> Option<(Range<usize>, Range<usize>)> {
+        let origin = &self.game_object.rect;
+        let size = PIXEL_SIZE as f32;
+
+        let to_cells = |start: f32, end: f32, len: usize| {
+            let first = (start / size).floor().max(0.0) as usize;
+            let last = ((end / size).ceil().max(0.0) as usize).min(len);
+
+            first..last
+        };
+
+        let cols = to_cells(
+            rect.x - origin.x,
+            rect.x + rect.width() - origin.x,
+            BARRIER_WIDTH,
+        );
+        let rows = to_cells(
+            rect.y - origin.y,
+            rect.y + rect.height() - origin.y,
+            BARRIER_HEIGHT,
+        );
+
+        if cols.is_empty() || rows.is_empty() {
+            return None;
         }
+
+        Some((cols, rows))
     }
 
-    pub fn get_colliders(barrier: &GameObject) -> Vec<Collider> {
-        let mut colliders = vec![];
-
-        let mut next_x;
-        let mut next_y;
-
-        let collider_heights_1 = [
-            [5, 5, 5],
-            [5, 5, 6],
-            [5, 6, 6],
-            [6, 6, 6],
-            [6, 6, 6],
-            [6, 6, 6],
-            [6, 6, 0],
-            [6, 5, 0],
-            [5, 5, 0],
-            [4, 5, 0],
-            [4, 4, 0],
-            [4, 4, 0],
-        ];
+    // Finds the first standing cell a shot runs into, taking the row nearest
+    // to where the shot comes from and the column nearest its centre.
+    pub fn hit(&self, rect: &FRect, from_below: bool) -> Option<(usize, usize)> {
+        let (cols, rows) = self.cells(rect)?;
+        let center = (rect.x + rect.width() / 2.0 - self.game_object.rect.x) / PIXEL_SIZE as f32;
+
+        let rows: Vec<usize> = if from_below {
+            rows.rev().collect()
+        } else {
+            rows.collect()
+        };
+
+        for row in rows {
+            let col = cols
+                .clone()
+                .filter(|col| self.pixels[row][*col] != 0)
+                .min_by(|a, b| {
+                    let a = (*a as f32 + 0.5 - center).abs();
+                    let b = (*b as f32 + 0.5 - center).abs();
+                    a.total_cmp(&b)
+                });
+
+            if let Some(col) = col {
+                return Some((col, row));
+            }
+        }
 
-        let mut collider_heights_2 = collider_heights_1.clone();
-        collider_heights_2.reverse();
+        None
+    }
 
-        let collider_heights = [collider_heights_1, collider_heights_2].concat();
+    // Clears the cells under a splash pattern centred on a cell.
+    pub fn carve<const W: usize, const H: usize>(
+        &mut self,
+        pattern: &[[u32; W]; H],
+        col: usize,
+        row: usize,
+    ) {
+        for (i, pattern_row) in pattern.iter().enumerate() {
+            for (j, value) in pattern_row.iter().enumerate() {
+                let y = (row + i).checked_sub(H / 2);
+                let x = (col + j).checked_sub(W / 2);
+
+                if let (Some(x), Some(y), true) = (x, y, *value != 0) {
+                    if x < BARRIER_WIDTH && y < BARRIER_HEIGHT {
+                        self.pixels[y][x] = 0;
+                    }
+                }
+            }
+        }
+    }
 
-        for (i, _) in BARRIER[0].iter().enumerate() {
-            if i == 0 || i == 23 {
-                next_y = barrier.rect.y + (3.0 * PIXEL_SIZE as f32);
-            } else if i == 1 || i == 22 {
-                next_y = barrier.rect.y + (2.0 * PIXEL_SIZE as f32);
-            } else if i == 2 || i == 21 {
-                next_y = barrier.rect.y + PIXEL_SIZE as f32;
-            } else {
-                next_y = barrier.rect.y;
+    // Clears every cell under a rectangle, as when invaders march through.
+    pub fn erase(&mut self, rect: &FRect) {
+        if let Some((cols, rows)) = self.cells(rect) {
+            for row in rows {
+                for col in cols.clone() {
+                    self.pixels[row][col] = 0;
+                }
             }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::texture_templates::BARRIER_MASK;
+
+    const P: f32 = PIXEL_SIZE as f32;
 
-            next_x = barrier.rect.x + (i as f32 * PIXEL_SIZE as f32);
+    // The barrier as it should look after carving `BARRIER_MASK` centred on
+    // a cell, worked out cell by cell.
+    fn carved(col: i32, row: i32) -> [[u32; BARRIER_WIDTH]; BARRIER_HEIGHT] {
+        let mut pixels = BARRIER;
 
-            for height in collider_heights[i] {
-                if height > 0 {
-                    colliders.push(Collider::new(next_x, next_y, height));
-                    next_y = next_y + (height as f32 * PIXEL_SIZE as f32);
+        for (y, pixel_row) in pixels.iter_mut().enumerate() {
+            for (x, pixel) in pixel_row.iter_mut().enumerate() {
+                let i = y as i32 - row + 2;
+                let j = x as i32 - col + 2;
+
+                if (0..5).contains(&i)
+                    && (0..5).contains(&j)
+                    && BARRIER_MASK[i as usize][j as usize] != 0
+                {
+                    *pixel = 0;
                 }
             }
         }
 
-        colliders
+        pixels
+    }
+
+    fn column(col: usize) -> FRect {
+        FRect::new(
+            col as f32 * P + 1.0,
+            -P,
+            2.0,
+            (BARRIER_HEIGHT + 2) as f32 * P,
+        )
+    }
+
+    #[test]
+    fn carving_clears_the_splash_pattern() {
+        let mut barrier = Barrier::new(0.0, 0.0);
+        barrier.carve(&BARRIER_MASK, 10, 8);
+
+        assert_eq!(barrier.pixels, carved(10, 8));
+        assert_ne!(barrier.pixels, BARRIER);
+    }
+
+    #[test]
+    fn carving_clips_at_the_edges() {
+        let corners = [
+            (0, 0),
+            (BARRIER_WIDTH - 1, 0),
+            (0, BARRIER_HEIGHT - 1),
+            (BARRIER_WIDTH - 1, BARRIER_HEIGHT - 1),
+        ];
+
+        for (col, row) in corners {
+            let mut barrier = Barrier::new(0.0, 0.0);
+            barrier.carve(&BARRIER_MASK, col, row);
+
+            assert_eq!(barrier.pixels, carved(col as i32, row as i32));
+        }
+    }
+
+    #[test]
+    fn shots_from_below_hit_the_bottom_first() {
+        let barrier = Barrier::new(0.0, 0.0);
+        let col = 4;
+        let mut rows = (0..BARRIER_HEIGHT).filter(|row| BARRIER[*row][col] != 0);
+        let top = rows.next().unwrap();
+        let bottom = rows.next_back().unwrap();
+
+        assert_eq!(barrier.hit(&column(col), false), Some((col, top)));
+        assert_eq!(barrier.hit(&column(col), true), Some((col, bottom)));
+    }
+
+    #[test]
+    fn eroded_barrier_stops_nothing() {
+        let mut barrier = Barrier::new(100.0, 200.0);
+        let rect = barrier.game_object.rect;
+        let mut shot = column(4);
+        shot.x += rect.x;
+        shot.y += rect.y;
+
+        assert!(barrier.hit(&shot, false).is_some());
+
+        barrier.erase(&rect);
+
+        assert!(barrier.pixels.iter().flatten().all(|pixel| *pixel == 0));
+
+        for col in 0..BARRIER_WIDTH {
+            let mut shot = column(col);
+            shot.x += rect.x;
+            shot.y += rect.y;
+
+            assert_eq!(barrier.hit(&shot, false), None);
+            assert_eq!(barrier.hit(&shot, true), None);
+        }
     }
 }
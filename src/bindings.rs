@@ -0,0 +1,212 @@
+use std::collections::{HashMap, HashSet};
+
+use sdl2::keyboard::Keycode;
+
+use space_invaders_rust::config::Config;
+
+const KEY_SEPARATOR: &str = ", ";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Fire,
+    Pause,
+    MenuUp,
+    MenuDown,
+    Confirm,
+    Back,
+    Mute,
+}
+
+pub const ACTIONS: [Action; 9] = [
+    Action::MoveLeft,
+    Action::MoveRight,
+    Action::Fire,
+    Action::Pause,
+    Action::MenuUp,
+    Action::MenuDown,
+    Action::Confirm,
+    Action::Back,
+    Action::Mute,
+];
+
+impl Action {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::MoveLeft => "MOVE LEFT",
+            Action::MoveRight => "MOVE RIGHT",
+            Action::Fire => "FIRE",
+            Action::Pause => "PAUSE",
+            Action::MenuUp => "MENU UP",
+            Action::MenuDown => "MENU DOWN",
+            Action::Confirm => "CONFIRM",
+            Action::Back => "BACK",
+            Action::Mute => "MUTE",
+        }
+    }
+
+    fn config_key(&self) -> &'static str {
+        match self {
+            Action::MoveLeft => "bind.move_left",
+            Action::MoveRight => "bind.move_right",
+            Action::Fire => "bind.fire",
+            Action::Pause => "bind.pause",
+            Action::MenuUp => "bind.menu_up",
+            Action::MenuDown => "bind.menu_down",
+            Action::Confirm => "bind.confirm",
+            Action::Back => "bind.back",
+            Action::Mute => "bind.mute",
+        }
+    }
+
+    // Gameplay and menu actions are never read at the same time, so a key
+    // only conflicts with another action from its own group. Mute works
+    // everywhere, so its keys can't be shared with anything.
+    fn is_gameplay(&self) -> bool {
+        matches!(
+            self,
+            Action::MoveLeft | Action::MoveRight | Action::Fire | Action::Pause
+        )
+    }
+
+    fn is_menu(&self) -> bool {
+        matches!(
+            self,
+            Action::MenuUp | Action::MenuDown | Action::Confirm | Action::Back
+        )
+    }
+
+    pub fn conflicts_with(&self, other: Action) -> bool {
+        *self != other
+            && (*self == Action::Mute
+                || other == Action::Mute
+                || self.is_gameplay() == other.is_gameplay())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bindings {
+    pub keys: HashMap<Action, Vec<Keycode>>,
+}
+
+impl Default for Bindings {
+    fn default() -> Bindings {
+        let keys = HashMap::from([
+            (Action::MoveLeft, vec![Keycode::Left, Keycode::A]),
+            (Action::MoveRight, vec![Keycode::Right, Keycode::D]),
+            (Action::Fire, vec![Keycode::Space]),
+            (Action::Pause, vec![Keycode::Escape, Keycode::P]),
+            (Action::MenuUp, vec![Keycode::Up]),
+            (Action::MenuDown, vec![Keycode::Down]),
+            (Action::Confirm, vec![Keycode::Return, Keycode::Space]),
+            (Action::Back, vec![Keycode::Escape, Keycode::Backspace]),
+            (Action::Mute, vec![Keycode::M]),
+        ]);
+
+        Bindings { keys }
+    }
+}
+
+impl Bindings {
+    pub fn load(config: &Config) -> Bindings {
+        let mut bindings = Bindings::default();
+
+        for action in ACTIONS {
+            let Some(value) = config.get(action.config_key()) else {
+                continue;
+            };
+
+            let keys: Vec<Keycode> = value
+                .split(KEY_SEPARATOR)
+                .filter_map(|name| {
+                    let key = Keycode::from_name(name);
+
+                    if key.is_none() {
+                        println!("{}: unknown key '{}'", action.config_key(), name);
+                    }
+
+                    key
+                })
+                .collect();
+
+            if !keys.is_empty() {
+                bindings.keys.insert(action, keys);
+            }
+        }
+
+        bindings
+    }
+
+    pub fn store(&self, config: &mut Config) {
+        for action in ACTIONS {
+            config.set(action.config_key(), &self.key_names(action));
+        }
+    }
+
+    pub fn key_names(&self, action: Action) -> String {
+        self.get(action)
+            .iter()
+            .map(|key| key.name())
+            .collect::<Vec<String>>()
+            .join(KEY_SEPARATOR)
+    }
+
+    pub fn get(&self, action: Action) -> &[Keycode] {
+        match self.keys.get(&action) {
+            Some(keys) => keys,
+            None => &[],
+        }
+    }
+
+    pub fn is_bound(&self, action: Action, key: Keycode) -> bool {
+        self.get(action).contains(&key)
+    }
+
+    pub fn is_menu_key(&self, key: Keycode) -> bool {
+        ACTIONS
+            .iter()
+            .any(|action| action.is_menu() && self.is_bound(*action, key))
+    }
+
+    pub fn actions(&self, keys: &HashSet<Keycode>) -> HashSet<Action> {
+        ACTIONS
+            .iter()
+            .filter(|action| self.get(**action).iter().any(|key| keys.contains(key)))
+            .copied()
+            .collect()
+    }
+
+    pub fn conflict(&self, action: Action, key: Keycode) -> Option<Action> {
+        ACTIONS
+            .iter()
+            .find(|other| action.conflicts_with(**other) && self.is_bound(**other, key))
+            .copied()
+    }
+
+    pub fn bind(&mut self, action: Action, key: Keycode) -> Result<(), String> {
+        if let Some(other) = self.conflict(action, key) {
+            return Err(format!("{} IS USED BY {}", key.name(), other.label()));
+        }
+
+        let keys = self.keys.entry(action).or_default();
+
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+
+        Ok(())
+    }
+
+    pub fn unbind(&mut self, action: Action, key: Keycode) -> Result<(), String> {
+        let keys = self.keys.entry(action).or_default();
+
+        if keys.len() <= 1 {
+            return Err(format!("{} NEEDS A KEY", action.label()));
+        }
+
+        keys.retain(|bound| *bound != key);
+
+        Ok(())
+    }
+}
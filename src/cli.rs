@@ -0,0 +1,229 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use space_invaders_rust::layout::LayoutMode;
+use space_invaders_rust::settings::{CollisionMode, Settings};
+
+const MAX_WINDOW_SIZE: u32 = 16_384;
+const MAX_START_WAVE: u32 = 99;
+
+pub const USAGE: &str = "\
+Usage: space-invaders-rust [OPTIONS]
+
+Options:
+      --windowed           Open in a window (default)
+      --fullscreen         Open fullscreen at the desktop resolution
+      --width <PIXELS>     Window width [default: fit the display]
+      --height <PIXELS>    Window height [default: fit the display]
+      --scale <MODE>       Scale to the window with 'fit' or 'integer' steps [default: fit]
+      --layout <LAYOUT>    Play on a 'landscape' field or the 'arcade' portrait screen [default: landscape]
+      --collision <MODE>   Test hits by bounding 'boxes' or sprite 'pixels' [default: boxes]
+      --seed <SEED>        Seed every new game with SEED instead of a random one
+      --start-wave <WAVE>  Start each game at WAVE [default: 1]
+      --lives <LIVES>      Lives at the start of each game [default: 3]
+      --mute               Start with sound muted
+      --replay <FILE>      Play back a recorded replay
+      --record <FILE>      Record each game to a replay file
+      --headless           Simulate without opening a window
+      --ticks <N>          Stop a headless run after N ticks [default: the replay's length, or 216000]
+      --config <FILE>      Read and write settings in FILE
+      --sprites <FILE>     Load sprite sheets listed in FILE [default: ./assets/sprites.txt]
+  -h, --help               Print this help
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scaling {
+    Fit,
+    Integer,
+}
+
+impl FromStr for Scaling {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "fit" => Ok(Scaling::Fit),
+            "integer" => Ok(Scaling::Integer),
+            _ => Err(String::from("expected 'fit' or 'integer'")),
+        }
+    }
+}
+
+fn parse_layout(value: &str) -> Result<LayoutMode, String> {
+    match value {
+        "landscape" => Ok(LayoutMode::Landscape),
+        "arcade" => Ok(LayoutMode::Arcade),
+        _ => Err(format!(
+            "invalid value '{}' for --layout: expected 'landscape' or 'arcade'",
+            value
+        )),
+    }
+}
+
+fn parse_collision(value: &str) -> Result<CollisionMode, String> {
+    CollisionMode::parse(value).ok_or_else(|| {
+        format!(
+            "invalid value '{}' for --collision: expected 'boxes' or 'pixels'",
+            value
+        )
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    pub fullscreen: bool,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub scaling: Scaling,
+    pub layout: Option<LayoutMode>,
+    pub collision: Option<CollisionMode>,
+    pub seed: Option<u64>,
+    pub start_wave: Option<u32>,
+    pub lives: Option<u32>,
+    pub mute: bool,
+    pub replay: Option<PathBuf>,
+    pub record: Option<PathBuf>,
+    pub headless: bool,
+    pub ticks: Option<u32>,
+    pub config: Option<PathBuf>,
+    pub sprites: Option<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            fullscreen: false,
+            width: None,
+            height: None,
+            scaling: Scaling::Fit,
+            layout: None,
+            collision: None,
+            seed: None,
+            start_wave: None,
+            lives: None,
+            mute: false,
+            replay: None,
+            record: None,
+            headless: false,
+            ticks: None,
+            config: None,
+            sprites: None,
+        }
+    }
+}
+
+impl Options {
+    pub fn seed(&self) -> u64 {
+        self.seed.unwrap_or_else(rand::random)
+    }
+
+    pub fn settings(&self) -> Settings {
+        let mut settings = Settings::default();
+
+        if let Some(lives) = self.lives {
+            settings.lives = lives;
+        }
+
+        if let Some(start_wave) = self.start_wave {
+            settings.start_wave = start_wave;
+        }
+
+        if let Some(layout) = self.layout {
+            settings.layout = layout;
+        }
+
+        if let Some(collision) = self.collision {
+            settings.collision = collision;
+        }
+
+        settings
+    }
+}
+
+pub enum Command {
+    Run(Options),
+    Help,
+}
+
+fn value<T: FromStr>(flag: &str, args: &mut impl Iterator<Item = String>) -> Result<T, String>
+where
+    T::Err: ToString,
+{
+    let value = args
+        .next()
+        .ok_or_else(|| format!("{} requires a value", flag))?;
+
+    value
+        .parse()
+        .map_err(|e: T::Err| format!("invalid value '{}' for {}: {}", value, flag, e.to_string()))
+}
+
+fn in_range(flag: &str, value: u32, min: u32, max: u32) -> Result<u32, String> {
+    if value < min || value > max {
+        return Err(format!("{} must be between {} and {}", flag, min, max));
+    }
+
+    Ok(value)
+}
+
+pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut options = Options::default();
+    let mut windowed = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--windowed" => windowed = true,
+            "--fullscreen" => options.fullscreen = true,
+            "--width" => {
+                options.width = Some(in_range(&arg, value(&arg, &mut args)?, 1, MAX_WINDOW_SIZE)?)
+            }
+            "--height" => {
+                options.height = Some(in_range(&arg, value(&arg, &mut args)?, 1, MAX_WINDOW_SIZE)?)
+            }
+            "--scale" => options.scaling = value(&arg, &mut args)?,
+            "--layout" => options.layout = Some(parse_layout(&value::<String>(&arg, &mut args)?)?),
+            "--collision" => {
+                options.collision = Some(parse_collision(&value::<String>(&arg, &mut args)?)?)
+            }
+            "--seed" => options.seed = Some(value(&arg, &mut args)?),
+            "--start-wave" => {
+                options.start_wave =
+                    Some(in_range(&arg, value(&arg, &mut args)?, 1, MAX_START_WAVE)?)
+            }
+            "--lives" => options.lives = Some(in_range(&arg, value(&arg, &mut args)?, 1, 99)?),
+            "--mute" => options.mute = true,
+            "--replay" => options.replay = Some(value(&arg, &mut args)?),
+            "--record" => options.record = Some(value(&arg, &mut args)?),
+            "--headless" => options.headless = true,
+            "--ticks" => options.ticks = Some(value(&arg, &mut args)?),
+            "--config" => options.config = Some(value(&arg, &mut args)?),
+            "--sprites" => options.sprites = Some(value(&arg, &mut args)?),
+            _ => return Err(format!("unknown option '{}'", arg)),
+        }
+    }
+
+    if windowed && options.fullscreen {
+        return Err(String::from(
+            "--windowed and --fullscreen can't be combined",
+        ));
+    }
+
+    if options.ticks.is_some() && !options.headless {
+        return Err(String::from("--ticks requires --headless"));
+    }
+
+    if options.replay.is_some()
+        && (options.seed.is_some()
+            || options.start_wave.is_some()
+            || options.lives.is_some()
+            || options.layout.is_some()
+            || options.collision.is_some())
+    {
+        return Err(String::from(
+            "--seed, --start-wave, --lives, --layout and --collision can't be combined with --replay",
+        ));
+    }
+
+    Ok(Command::Run(options))
+}
@@ -1,66 +1,194 @@
-use crate::game::Game;
+use crate::game::{Game, GameObject, PIXEL_SIZE};
+use crate::grid::Grid;
+use crate::layout::Layout;
+use crate::mask::{pixels_overlap, sprite_mask};
+use crate::player::Player;
+use crate::rect::FRect;
+use crate::settings::CollisionMode;
+use crate::texture_templates::{BARRIER_MASK, INVADER_SHOT_EXPLOSION};
+use crate::ufo::mystery_points;
 use crate::util::overlaps;
-use crate::Player;
 
-pub fn update(player: &mut Player, game: &mut Game) {
+// The animation frame of sprites that don't animate.
+const STILL: i32 = 1;
+// About one invader with its spacing, so most sprites touch a few cells.
+const CELL_SIZE: f32 = 16.0 * PIXEL_SIZE as f32;
+
+fn collides(
+    mode: CollisionMode,
+    a: &GameObject,
+    a_frame: i32,
+    b: &GameObject,
+    b_frame: i32,
+) -> bool {
+    match mode {
+        CollisionMode::Boxes => overlaps(&a.rect, &b.rect),
+        CollisionMode::Pixels => pixels_overlap(
+            &a.rect,
+            sprite_mask(&a.texture_name, a_frame),
+            &b.rect,
+            sprite_mask(&b.texture_name, b_frame),
+        ),
+    }
+}
+
+// Everything shots and invaders are tested against, besides the player and
+// the UFO, which are single objects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Entry {
+    Invader(usize),
+    Barrier(usize),
+    Bullet(usize),
+}
+
+pub struct Broadphase {
+    grid: Grid<Entry>,
+    found: Vec<Entry>,
+}
+
+impl Broadphase {
+    pub fn new(layout: &Layout) -> Broadphase {
+        Broadphase {
+            grid: Grid::new(layout.width as f32, layout.height as f32, CELL_SIZE),
+            found: vec![],
+        }
+    }
+
+    fn build(&mut self, player: &Player, game: &Game) {
+        self.grid.clear();
+
+        for (i, invader) in game.invaders.iter().enumerate() {
+            self.grid
+                .insert(Entry::Invader(i), &invader.game_object.rect);
+        }
+
+        for (i, barrier) in game.barrier_row.iter().enumerate() {
+            self.grid
+                .insert(Entry::Barrier(i), &barrier.game_object.rect);
+        }
+
+        for (i, bullet) in player.bullets.iter().enumerate() {
+            self.grid.insert(Entry::Bullet(i), &bullet.rect);
+        }
+    }
+}
+
+// Only tests the pairs that share a cell of the broadphase grid.
+pub fn update(broadphase: &mut Broadphase, player: &mut Player, game: &mut Game) {
+    broadphase.build(player, game);
+
+    let Broadphase { grid, found } = broadphase;
+    resolve(player, game, found, |rect, found| grid.query(rect, found));
+}
+
+// Tests every pair; kept to check the broadphase against.
+pub fn update_brute_force(player: &mut Player, game: &mut Game) {
+    let entries: Vec<Entry> = (0..game.invaders.len())
+        .map(Entry::Invader)
+        .chain((0..game.barrier_row.len()).map(Entry::Barrier))
+        .chain((0..player.bullets.len()).map(Entry::Bullet))
+        .collect();
+
+    resolve(player, game, &mut vec![], |_, found| {
+        found.clone_from(&entries);
+    });
+}
+
+// `candidates` lists what a rect might touch, sorted, so that hits are
+// resolved in the same order whichever way the list was found.
+fn resolve(
+    player: &mut Player,
+    game: &mut Game,
+    found: &mut Vec<Entry>,
+    mut candidates: impl FnMut(&FRect, &mut Vec<Entry>),
+) {
+    let mode = game.settings.collision;
+
     'bullets: for bullet in &mut player.bullets {
-        for invader in &mut game.invaders {
-            if overlaps(&invader.game_object.rect, &bullet.rect) {
-                invader.game_object.is_destroyed = true;
-                bullet.is_destroyed = true;
-                continue 'bullets;
+        candidates(&bullet.rect, found);
+
+        for entry in found.iter() {
+            if let Entry::Invader(i) = *entry {
+                let invader = &mut game.invaders[i];
+
+                if collides(mode, &invader.game_object, invader.anim_step, bullet, STILL) {
+                    invader.game_object.is_destroyed = true;
+                    bullet.is_destroyed = true;
+                    continue 'bullets;
+                }
             }
         }
 
-        for barrier in &mut game.barrier_row {
-            for collider in &mut barrier.colliders {
-                if !collider.is_destroyed && overlaps(&collider.rect, &bullet.rect) {
-                    collider.is_destroyed = true;
+        for entry in found.iter() {
+            if let Entry::Barrier(i) = *entry {
+                let barrier = &mut game.barrier_row[i];
+
+                if let Some((col, row)) = barrier.hit(&bullet.rect, true) {
+                    barrier.carve(&BARRIER_MASK, col, row);
                     bullet.is_destroyed = true;
                     continue 'bullets;
                 }
             }
         }
 
-        if game.ufo_active && overlaps(&game.ufo.game_object.rect, &bullet.rect) {
+        if game.ufo_active && collides(mode, &game.ufo.game_object, STILL, bullet, STILL) {
             game.ufo.game_object.is_destroyed = true;
+            game.ufo.points = mystery_points(player.shots_fired);
             bullet.is_destroyed = true;
             continue;
         }
     }
 
-    for invader in &mut game.invaders {
-        if overlaps(&invader.game_object.rect, &player.game_object.rect) {
+    for invader in &game.invaders {
+        if collides(
+            mode,
+            &invader.game_object,
+            invader.anim_step,
+            &player.game_object,
+            STILL,
+        ) {
             player.game_object.is_destroyed = true;
         }
+    }
 
-        for barrier in &mut game.barrier_row {
-            for collider in &mut barrier.colliders {
-                if !collider.is_destroyed && overlaps(&collider.rect, &invader.game_object.rect) {
-                    collider.is_destroyed = true;
-                }
+    // Erasing is the same in any order, so look up the invaders over each
+    // barrier rather than the barriers under each invader.
+    for barrier in &mut game.barrier_row {
+        candidates(&barrier.game_object.rect, found);
+
+        for entry in found.iter() {
+            if let Entry::Invader(i) = *entry {
+                barrier.erase(&game.invaders[i].game_object.rect);
             }
         }
     }
 
     'invader_shots: for invader_shot in &mut game.invader_shots {
-        if overlaps(&invader_shot.rect, &player.game_object.rect) {
+        if collides(mode, invader_shot, STILL, &player.game_object, STILL) {
             player.game_object.is_destroyed = true;
             continue;
         }
 
-        for bullet in &mut player.bullets {
-            if overlaps(&invader_shot.rect, &bullet.rect) {
-                invader_shot.is_destroyed = true;
-                bullet.is_destroyed = true;
-                continue 'invader_shots;
+        candidates(&invader_shot.rect, found);
+
+        for entry in found.iter() {
+            if let Entry::Bullet(i) = *entry {
+                let bullet = &mut player.bullets[i];
+
+                if collides(mode, invader_shot, STILL, bullet, STILL) {
+                    invader_shot.is_destroyed = true;
+                    bullet.is_destroyed = true;
+                    continue 'invader_shots;
+                }
             }
         }
 
-        for barrier in &mut game.barrier_row {
-            for collider in &mut barrier.colliders {
-                if !collider.is_destroyed && overlaps(&collider.rect, &invader_shot.rect) {
-                    collider.is_destroyed = true;
+        for entry in found.iter() {
+            if let Entry::Barrier(i) = *entry {
+                let barrier = &mut game.barrier_row[i];
+
+                if let Some((col, row)) = barrier.hit(&invader_shot.rect, false) {
+                    barrier.carve(&INVADER_SHOT_EXPLOSION, col, row);
                     invader_shot.is_destroyed = true;
                     continue 'invader_shots;
                 }
@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::util::config_dir;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    values: BTreeMap<String, String>,
+}
+
+impl Config {
+    pub fn default_path() -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join("config.txt"))
+    }
+
+    pub fn load(path: &Path) -> Config {
+        match fs::read_to_string(path) {
+            Ok(contents) => Config::parse(&contents, path),
+            Err(_) => Config::default(),
+        }
+    }
+
+    fn parse(contents: &str, path: &Path) -> Config {
+        let mut config = Config::default();
+
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            match line.split_once('=') {
+                Some((key, value)) => config.set(key.trim(), value.trim()),
+                None => println!("{}: line {}: expected key = value", path.display(), i + 1),
+            }
+        }
+
+        config
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+
+        let contents: String = self
+            .values
+            .iter()
+            .map(|(key, value)| format!("{} = {}\n", key, value))
+            .collect();
+
+        fs::write(path, contents).map_err(|e| e.to_string())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(|value| value.as_str())
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) {
+        self.values.insert(String::from(key), String::from(value));
+    }
+}
@@ -0,0 +1,50 @@
+use sdl2::pixels::Color;
+use sdl2::rect::{FRect, Rect};
+use sdl2::render::{BlendMode, Canvas, Texture};
+use sdl2::video::Window;
+
+use space_invaders_rust::layout::Layout;
+use space_invaders_rust::rect;
+
+pub fn to_sdl_rect(rect: &rect::FRect) -> FRect {
+    FRect::new(rect.x, rect.y, rect.width(), rect.height())
+}
+
+pub fn canvas_size(canvas: &Canvas<Window>) -> (i32, i32) {
+    let (width, height) = canvas.logical_size();
+    (width as i32, height as i32)
+}
+
+// Multiplies everything already drawn in each band by the band's colour.
+pub fn draw_overlay(canvas: &mut Canvas<Window>, layout: &Layout) -> Result<(), String> {
+    canvas.set_blend_mode(BlendMode::Mod);
+
+    for band in layout.overlay {
+        let (r, g, b) = band.color;
+
+        canvas.set_draw_color(Color::RGB(r, g, b));
+        canvas.fill_rect(Rect::new(
+            0,
+            band.top,
+            layout.width as u32,
+            (band.bottom - band.top) as u32,
+        ))?;
+    }
+
+    canvas.set_blend_mode(BlendMode::None);
+
+    Ok(())
+}
+
+pub fn draw_texture_nameless_rect<'a>(
+    canvas: &mut Canvas<Window>,
+    texture: &Texture<'a>,
+    rect: &Rect,
+) {
+    let result = canvas.copy(texture, None, *rect);
+
+    match result {
+        Ok(_) => (),
+        Err(err) => println!("{}", err),
+    }
+}
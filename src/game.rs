@@ -1,37 +1,31 @@
 use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
 
-use sdl2::rect::FRect;
+use crate::rect::FRect;
 
-use rand::{self, thread_rng, Rng};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
 use crate::barrier::Barrier;
 use crate::invader::Invader;
+use crate::layout::Layout;
+use crate::settings::{BarrierRule, Settings};
+use crate::timer::Timer;
 use crate::ufo::UFO;
-use crate::util::decrease_until_zero;
-use crate::Timer;
+use crate::util::{decrease_until_zero, Fnv1a};
 
 pub const FPS: u32 = 60;
+pub const DELTA_TIME: f32 = 1000.0 / FPS as f32;
 
 pub const PIXEL_SIZE: i32 = 6;
-pub const CANVAS_WIDTH: i32 = 1920;
-pub const CANVAS_HEIGHT: i32 = 1080;
-pub const CANVAS_RIGHT_EDGE: i32 = CANVAS_WIDTH - WIDTH_DIV_20 - 12 * PIXEL_SIZE;
-pub const CANVAS_LEFT_EDGE: i32 = WIDTH_DIV_20;
 
 const ROW_SIZE: u32 = 11;
 
-const WIDTH_DIV_4: i32 = CANVAS_WIDTH / 4;
-const WIDTH_DIV_20: i32 = CANVAS_WIDTH / 20;
-const WIDTH_DIV_24: i32 = CANVAS_WIDTH / 24;
-const WIDTH_DIV_80: i32 = CANVAS_WIDTH / 80;
-const WIDTH_DIV_240: i32 = CANVAS_WIDTH / 240;
-const WIDTH_DIV_320: i32 = CANVAS_WIDTH / 320;
-
-pub const HEIGHT_DIV_4: i32 = CANVAS_HEIGHT / 4;
-
 const INVADER_SHOT_DELAY: u32 = 10;
 const EXPLOSION_TIMER: i32 = 2;
 
+const MARCH_NOTES: u8 = 4;
+
 const DEFAULT_TICK: i32 = 50;
 const TICK_INCREASE: i32 = 12;
 const SPEED_INCREASE_LEN: i32 = 15;
@@ -39,18 +33,37 @@ const SPEED_INCREASE_LEN: i32 = 15;
 #[derive(Debug)]
 pub struct GameObject {
     pub rect: FRect,
+    pub prev_rect: FRect,
     pub texture_name: String,
     pub is_destroyed: bool,
 }
 
 impl GameObject {
     pub fn new(x: f32, y: f32, width: u32, height: u32, texture_name: String) -> Self {
+        let rect = FRect::new(x, y, width as f32, height as f32);
+
         GameObject {
-            rect: FRect::new(x, y, width as f32, height as f32),
+            rect,
+            prev_rect: rect,
             texture_name,
             is_destroyed: false,
         }
     }
+
+    pub fn store_prev_rect(&mut self) {
+        self.prev_rect = self.rect;
+    }
+
+    pub fn interpolated_rect(&self, alpha: f32) -> FRect {
+        self.prev_rect.lerp(&self.rect, alpha)
+    }
+}
+
+impl Hash for GameObject {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rect.hash(state);
+        self.is_destroyed.hash(state);
+    }
 }
 
 pub struct Game {
@@ -70,113 +83,96 @@ pub struct Game {
     ufo_spawn_times: u32,
     invader_tick: i32,
     speed_increase_threashold: i32,
+    pub seed: u64,
+    // ChaCha8 rather than `StdRng`, whose algorithm may change between rand
+    // releases and platforms; seeded games and replays need the same
+    // sequence everywhere.
+    rng: ChaCha8Rng,
+    pub score: u32,
+    pub lives: u32,
+    extra_life_awarded: bool,
+    pub settings: Settings,
+    pub layout: Layout,
+    pub wave: u32,
+    pub events: Vec<GameEvent>,
+    march_note: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameEvent {
+    PlayerShot,
+    InvaderMarch(u8),
+    InvaderKilled,
+    PlayerKilled,
+    UfoKilled,
 }
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Hash)]
 pub enum State {
+    Title,
     Playing,
     Paused,
     GameOver,
+    Invaded,
     Win,
 }
 
-impl Game {
-    pub fn new() -> Self {
-        let mut invaders = vec![];
-
-        let mut barrier_row = vec![];
-
-        let mut cur_x = WIDTH_DIV_4;
-        let mut cur_y = CANVAS_HEIGHT / 6;
-
-        for i in 0..ROW_SIZE {
-            invaders.push(Invader::new(
-                cur_x as f32,
-                cur_y as f32,
-                8 * PIXEL_SIZE,
-                8 * PIXEL_SIZE,
-                String::from("invader_texture1"),
-                4,
-                i,
-            ));
-
-            cur_x += (8 * PIXEL_SIZE) + WIDTH_DIV_80 * 2;
-        }
-
-        cur_x = WIDTH_DIV_4;
-        cur_y += WIDTH_DIV_20;
-
-        for i in 0..ROW_SIZE {
-            invaders.push(Invader::new(
-                cur_x as f32,
-                cur_y as f32,
-                11 * PIXEL_SIZE,
-                8 * PIXEL_SIZE,
-                String::from("invader_texture2"),
-                3,
-                i,
-            ));
-
-            cur_x += (11 * PIXEL_SIZE) + WIDTH_DIV_80 + WIDTH_DIV_320;
-        }
+impl State {
+    pub fn is_finished(&self) -> bool {
+        *self == State::GameOver || *self == State::Invaded || *self == State::Win
+    }
+}
 
-        cur_x = WIDTH_DIV_4 - WIDTH_DIV_240;
-        cur_y += WIDTH_DIV_20;
+// Rows from the top: sprite width, texture and row number.
+const FORMATION: [(i32, &str, u32); 5] = [
+    (8, "invader_texture1", 4),
+    (11, "invader_texture2", 3),
+    (11, "invader_texture2", 2),
+    (12, "invader_texture3", 1),
+    (12, "invader_texture3", 0),
+];
 
-        for i in 0..ROW_SIZE {
-            invaders.push(Invader::new(
-                cur_x as f32,
-                cur_y as f32,
-                11 * PIXEL_SIZE,
-                8 * PIXEL_SIZE,
-                String::from("invader_texture2"),
-                2,
-                i,
-            ));
+fn create_invaders(layout: &Layout, wave: u32) -> Vec<Invader> {
+    let mut invaders = vec![];
 
-            cur_x += (11 * PIXEL_SIZE) + WIDTH_DIV_80 + WIDTH_DIV_320;
-        }
+    let mut cur_y = layout.formation_y + layout.wave_start_offset(wave);
 
-        cur_x = WIDTH_DIV_4 - WIDTH_DIV_240;
-        cur_y += WIDTH_DIV_20;
+    for ((width, texture_name, row), formation_row) in FORMATION.iter().zip(layout.formation_rows) {
+        let mut cur_x = formation_row.x;
 
         for i in 0..ROW_SIZE {
             invaders.push(Invader::new(
                 cur_x as f32,
                 cur_y as f32,
-                12 * PIXEL_SIZE,
+                width * PIXEL_SIZE,
                 8 * PIXEL_SIZE,
-                String::from("invader_texture3"),
-                1,
+                String::from(*texture_name),
+                *row,
                 i,
             ));
 
-            cur_x += (12 * PIXEL_SIZE) + WIDTH_DIV_80;
+            cur_x += formation_row.step;
         }
 
-        cur_x = WIDTH_DIV_4 - WIDTH_DIV_240;
-        cur_y += WIDTH_DIV_20;
-
-        for i in 0..ROW_SIZE {
-            invaders.push(Invader::new(
-                cur_x as f32,
-                cur_y as f32,
-                12 * PIXEL_SIZE,
-                8 * PIXEL_SIZE,
-                String::from("invader_texture3"),
-                0,
-                i,
-            ));
+        cur_y += layout.row_spacing;
+    }
 
-            cur_x += (12 * PIXEL_SIZE) + WIDTH_DIV_80;
-        }
+    invaders
+}
 
-        let mut barrier_x = WIDTH_DIV_24 as f32 * 2.0;
+fn create_barriers(layout: &Layout) -> Vec<Barrier> {
+    layout
+        .barrier_xs
+        .iter()
+        .map(|x| Barrier::new(*x as f32, layout.barrier_y as f32))
+        .collect()
+}
 
-        for _i in 0..4 {
-            barrier_row.push(Barrier::new(barrier_x));
-            barrier_x += WIDTH_DIV_4 as f32;
-        }
+impl Game {
+    pub fn new(seed: u64, settings: Settings) -> Self {
+        let layout = Layout::new(settings.layout);
+        let invaders = create_invaders(&layout, settings.start_wave);
+        let barrier_row = create_barriers(&layout);
 
         Game {
             invaders,
@@ -189,12 +185,22 @@ impl Game {
             invader_timer: 0,
             speed: 1,
             move_rows_down: VecDeque::new(),
-            ufo: UFO::new(0),
+            ufo: UFO::new(0, &layout),
             spawn_ufo: false,
             ufo_active: false,
             ufo_spawn_times: 0,
             invader_tick: DEFAULT_TICK,
             speed_increase_threashold: DEFAULT_TICK - SPEED_INCREASE_LEN,
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            score: 0,
+            lives: settings.lives,
+            extra_life_awarded: false,
+            settings,
+            layout,
+            wave: settings.start_wave,
+            events: vec![],
+            march_note: 0,
         }
     }
 
@@ -213,7 +219,7 @@ impl Game {
         columns
     }
 
-    fn get_invader_shooters(&self) -> Vec<i32> {
+    fn get_invader_shooters(&mut self) -> Vec<i32> {
         let invader_indices = self.get_last_invader_per_column();
 
         let mut max = 4;
@@ -222,11 +228,11 @@ impl Game {
         }
 
         if max > 1 {
-            let num = thread_rng().gen_range(1..max);
+            let num = self.rng.gen_range(1..max);
 
             let shooters: Vec<i32> = (0..num)
                 .map(|_| {
-                    let index = thread_rng().gen_range(0..invader_indices.len());
+                    let index = self.rng.gen_range(0..invader_indices.len());
                     invader_indices[index]
                 })
                 .collect();
@@ -237,12 +243,52 @@ impl Game {
         vec![]
     }
 
+    // Invaders are left out: they are drawn at their current step.
+    pub fn store_prev_rects(&mut self) {
+        for shot in &mut self.invader_shots {
+            shot.store_prev_rect();
+        }
+
+        self.ufo.game_object.store_prev_rect();
+    }
+
+    pub fn checksum(&self) -> u64 {
+        let mut hasher = Fnv1a::default();
+
+        self.state.hash(&mut hasher);
+        self.score.hash(&mut hasher);
+        self.lives.hash(&mut hasher);
+        self.wave.hash(&mut hasher);
+        self.invader_timer.hash(&mut hasher);
+        self.invader_tick.hash(&mut hasher);
+        self.ufo_active.hash(&mut hasher);
+        self.ufo.game_object.hash(&mut hasher);
+        self.invader_shots.hash(&mut hasher);
+
+        for invader in &self.invaders {
+            invader.game_object.hash(&mut hasher);
+            invader.anim_step.hash(&mut hasher);
+        }
+
+        for barrier in &self.barrier_row {
+            for pixel in barrier.pixels.as_flattened() {
+                pixel.hash(&mut hasher);
+            }
+        }
+
+        hasher.finish()
+    }
+
+    pub fn set_title(&mut self) {
+        self.state = State::Title;
+    }
+
     pub fn set_playing(&mut self) {
         self.state = State::Playing;
     }
 
     pub fn set_paused(&mut self) {
-        if self.state != State::GameOver {
+        if self.state == State::Playing {
             self.state = State::Paused;
         }
     }
@@ -251,14 +297,64 @@ impl Game {
         self.state = State::GameOver;
     }
 
-    pub fn get_next_ufo_time(&self) -> u32 {
-        let adjust: i32 = thread_rng().gen_range(-5..=5);
+    pub fn set_invaded(&mut self) {
+        self.lives = 0;
+        self.state = State::Invaded;
+    }
+
+    pub fn set_win(&mut self) {
+        self.state = State::Win;
+    }
+
+    fn next_wave(&mut self) {
+        if let Some(waves) = self.settings.waves {
+            if self.wave >= waves {
+                self.set_win();
+                return;
+            }
+        }
+
+        self.wave = self.wave.saturating_add(1);
+        self.invaders = create_invaders(&self.layout, self.wave);
+
+        if self.settings.barrier_rule == BarrierRule::Restore {
+            self.barrier_row = create_barriers(&self.layout);
+        }
+
+        self.clear_invader_shots();
+        self.move_rows_down.clear();
+        self.invader_timer = 0;
+        self.invader_shot_timer = 0;
+        self.invader_tick = DEFAULT_TICK;
+        self.speed_increase_threashold = DEFAULT_TICK - SPEED_INCREASE_LEN;
+    }
+
+    pub fn lose_life(&mut self) {
+        self.lives = self.lives.saturating_sub(1);
+    }
+
+    fn check_extra_life(&mut self) {
+        if let Some(extra_life_score) = self.settings.extra_life_score {
+            if !self.extra_life_awarded && self.score >= extra_life_score {
+                self.lives += 1;
+                self.extra_life_awarded = true;
+            }
+        }
+    }
+
+    pub fn clear_invader_shots(&mut self) {
+        self.invader_shots.clear();
+        self.loaded_shot.clear();
+    }
+
+    pub fn get_next_ufo_time(&mut self) -> u32 {
+        let adjust: i32 = self.rng.gen_range(-5..=5);
         let next = 30 + adjust;
-        next as u32 * FPS as u32
+        next as u32 * FPS
     }
 
     pub fn toggle_spawn_ufo(&mut self) {
-        if self.spawn_ufo == false {
+        if !self.spawn_ufo {
             self.ufo_spawn_times += 1;
         }
 
@@ -270,12 +366,11 @@ impl Game {
             self.toggle_spawn_ufo();
         }
 
-        if timer.game_over_timer > 1 {
-            self.set_game_over();
-        }
-
         for invader in &self.invaders {
             if invader.game_object.is_destroyed {
+                self.score += invader.points();
+                self.events.push(GameEvent::InvaderKilled);
+
                 self.explosions.push((
                     GameObject::new(
                         invader.game_object.rect.x,
@@ -291,6 +386,12 @@ impl Game {
 
         self.invaders.retain(|r| !r.game_object.is_destroyed);
 
+        self.check_extra_life();
+
+        if self.invaders.is_empty() {
+            self.next_wave();
+        }
+
         if self.invaders.len() as i32 <= self.speed_increase_threashold {
             self.invader_tick = decrease_until_zero(self.invader_tick, TICK_INCREASE, 0);
 
@@ -301,11 +402,11 @@ impl Game {
         if self.spawn_ufo {
             self.toggle_spawn_ufo();
             self.ufo_active = true;
-            self.ufo = UFO::new(self.ufo_spawn_times);
+            self.ufo = UFO::new(self.ufo_spawn_times, &self.layout);
         }
 
-        if self.ufo.game_object.rect.x >= CANVAS_RIGHT_EDGE as f32 && self.ufo.dir == "right"
-            || self.ufo.game_object.rect.x <= CANVAS_LEFT_EDGE as f32 && self.ufo.dir == "left"
+        if self.ufo.game_object.rect.x >= self.layout.right_edge as f32 && self.ufo.dir == "right"
+            || self.ufo.game_object.rect.x <= self.layout.left_edge as f32 && self.ufo.dir == "left"
         {
             self.ufo_active = false;
         }
@@ -314,8 +415,11 @@ impl Game {
             self.ufo.move_x();
         }
 
-        if self.ufo.game_object.is_destroyed && self.ufo_active == true {
+        if self.ufo.game_object.is_destroyed && self.ufo_active {
             self.ufo_active = false;
+            self.score += self.ufo.points;
+            self.events.push(GameEvent::UfoKilled);
+            self.check_extra_life();
 
             self.explosions.push((
                 GameObject::new(
@@ -353,26 +457,19 @@ impl Game {
             shot.rect.y += 10.0;
         }
 
-        self.invader_timer += 1 * self.speed;
+        self.invader_timer += self.speed;
 
         if self.invader_timer >= self.invader_tick {
             self.invader_timer = 0;
 
             let mut move_down = false;
-            if self.move_rows_down.len() == 0 {
-                for invader in &self.invaders {
-                    if invader.game_object.rect.x >= CANVAS_RIGHT_EDGE as f32
+            if self.move_rows_down.is_empty() {
+                move_down = self.invaders.iter().any(|invader| {
+                    invader.game_object.rect.x >= self.layout.right_edge as f32
                         && invader.dir == "right"
-                    {
-                        move_down = true;
-                        break;
-                    } else if invader.game_object.rect.x <= CANVAS_LEFT_EDGE as f32
-                        && invader.dir == "left"
-                    {
-                        move_down = true;
-                        break;
-                    }
-                }
+                        || invader.game_object.rect.x <= self.layout.left_edge as f32
+                            && invader.dir == "left"
+                });
             }
 
             if move_down {
@@ -383,18 +480,23 @@ impl Game {
                 }
             }
 
-            if self.move_rows_down.len() == 0 {
+            let mut moved = false;
+
+            if self.move_rows_down.is_empty() {
                 for invader in &mut self.invaders {
                     if invader.dir == "right" {
                         invader.move_x_right();
                     } else {
                         invader.move_x_left();
                     }
+
+                    moved = true;
                 }
             } else {
                 for invader in &mut self.invaders {
                     if invader.row == self.move_rows_down[0] {
                         invader.move_down();
+                        moved = true;
 
                         if invader.dir == "right" {
                             invader.dir = String::from("left");
@@ -407,6 +509,20 @@ impl Game {
                 self.move_rows_down.pop_front();
             }
 
+            // A row queued to drop may have been shot out in the meantime,
+            // leaving nothing to step to.
+            if moved {
+                self.events.push(GameEvent::InvaderMarch(self.march_note));
+                self.march_note = (self.march_note + 1) % MARCH_NOTES;
+            }
+
+            if self.invaders.iter().any(|invader| {
+                invader.game_object.rect.y + invader.game_object.rect.height()
+                    >= self.layout.invasion_line() as f32
+            }) {
+                self.set_invaded();
+            }
+
             self.invader_shot_timer += 1;
 
             if self.invader_shot_timer >= INVADER_SHOT_DELAY {
@@ -421,20 +537,17 @@ impl Game {
             }
 
             for shot in &self.loaded_shot {
-                if shot.1 <= timer.time {
-                    if self.invaders.len() > shot.0 as usize {
-                        let invader = &self.invaders[shot.0 as usize];
-
-                        if !invader.game_object.is_destroyed {
-                            self.invader_shots.push(GameObject::new(
-                                invader.game_object.rect.x
-                                    + (invader.game_object.rect.width() / 2.0),
-                                invader.game_object.rect.y + invader.game_object.rect.height(),
-                                3 * PIXEL_SIZE as u32,
-                                7 * PIXEL_SIZE as u32,
-                                String::from("invader_shot_texture"),
-                            ));
-                        }
+                if shot.1 <= timer.time && self.invaders.len() > shot.0 as usize {
+                    let invader = &self.invaders[shot.0 as usize];
+
+                    if !invader.game_object.is_destroyed {
+                        self.invader_shots.push(GameObject::new(
+                            invader.game_object.rect.x + (invader.game_object.rect.width() / 2.0),
+                            invader.game_object.rect.y + invader.game_object.rect.height(),
+                            3 * PIXEL_SIZE as u32,
+                            7 * PIXEL_SIZE as u32,
+                            String::from("invader_shot_texture"),
+                        ));
                     }
                 }
             }
@@ -443,3 +556,152 @@ impl Game {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::LayoutMode;
+    use crate::player::Player;
+
+    #[test]
+    fn every_wave_starts_above_the_barriers() {
+        for layout in [LayoutMode::Landscape, LayoutMode::Arcade] {
+            for start_wave in (0..=20).chain([u32::MAX]) {
+                let settings = Settings {
+                    layout,
+                    start_wave,
+                    ..Settings::default()
+                };
+                let game = Game::new(0, settings);
+
+                let bottom = game
+                    .invaders
+                    .iter()
+                    .map(|invader| invader.game_object.rect.y + invader.game_object.rect.height())
+                    .fold(f32::MIN, f32::max);
+
+                assert!(
+                    bottom < game.layout.barrier_y as f32,
+                    "{:?} wave {} starts at {}, barriers at {}",
+                    layout,
+                    start_wave,
+                    bottom,
+                    game.layout.barrier_y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn invasion_line_is_the_players_baseline() {
+        for mode in [LayoutMode::Landscape, LayoutMode::Arcade] {
+            let layout = Layout::new(mode);
+            let player = Player::new(&layout);
+
+            assert_eq!(
+                player.game_object.rect.y + player.game_object.rect.height(),
+                layout.invasion_line() as f32
+            );
+        }
+    }
+
+    // Keeps one invader from each of the top three rows, shoots out a row
+    // while it is queued to move down, and checks that a march note
+    // comes with every step and only with a step.
+    #[test]
+    fn march_plays_only_when_the_formation_moves() {
+        let mut game = Game::new(0, Settings::default());
+        let player = Player::new(&game.layout);
+        let mut timer = Timer::new(&mut game);
+
+        game.invaders
+            .retain(|invader| invader.column == 0 && invader.row >= 2);
+        game.invader_tick = 0;
+        game.speed_increase_threashold = 0;
+
+        let positions = |game: &Game| -> Vec<(f32, f32)> {
+            game.invaders
+                .iter()
+                .map(|invader| (invader.game_object.rect.x, invader.game_object.rect.y))
+                .collect()
+        };
+
+        let mut shot_out = false;
+
+        for _ in 0..600 {
+            if !shot_out && game.move_rows_down.len() == 2 {
+                let row = game.move_rows_down[0];
+                game.invaders.retain(|invader| invader.row != row);
+                shot_out = true;
+            }
+
+            game.events.clear();
+            let before = positions(&game);
+
+            game.update(&timer);
+            timer.update(&mut game, &player);
+
+            let notes = game
+                .events
+                .iter()
+                .filter(|event| matches!(event, GameEvent::InvaderMarch(_)))
+                .count();
+
+            assert_eq!(notes, usize::from(positions(&game) != before));
+        }
+
+        assert!(shot_out);
+    }
+
+    #[test]
+    fn extra_life_is_granted_once_at_the_threshold() {
+        let mut game = Game::new(1, Settings::default());
+
+        game.score = 1490;
+        game.check_extra_life();
+
+        assert_eq!(game.lives, 3);
+
+        game.score = 1500;
+        game.check_extra_life();
+
+        assert_eq!(game.lives, 4);
+
+        game.score = 3000;
+        game.check_extra_life();
+        game.lose_life();
+        game.check_extra_life();
+
+        assert_eq!(game.lives, 3);
+    }
+
+    #[test]
+    fn no_extra_life_without_a_threshold() {
+        let mut game = Game::new(
+            1,
+            Settings {
+                extra_life_score: None,
+                ..Settings::default()
+            },
+        );
+
+        game.score = u32::MAX;
+        game.check_extra_life();
+
+        assert_eq!(game.lives, 3);
+    }
+
+    #[test]
+    fn wave_number_saturates_instead_of_overflowing() {
+        let settings = Settings {
+            start_wave: u32::MAX,
+            ..Settings::default()
+        };
+        let mut game = Game::new(0, settings);
+
+        game.next_wave();
+
+        assert_eq!(game.wave, u32::MAX);
+        assert_eq!(game.invaders.len(), 55);
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::{HashMap, HashSet};
+
+use sdl2::controller::{Axis, Button, GameController};
+use sdl2::event::Event;
+use sdl2::GameControllerSubsystem;
+
+use crate::bindings::Action;
+
+const STICK_DEADZONE: i16 = 8_000;
+
+const BUTTONS: [(Button, Action); 9] = [
+    (Button::DPadLeft, Action::MoveLeft),
+    (Button::DPadRight, Action::MoveRight),
+    (Button::DPadUp, Action::MenuUp),
+    (Button::DPadDown, Action::MenuDown),
+    (Button::A, Action::Fire),
+    (Button::A, Action::Confirm),
+    (Button::X, Action::Fire),
+    (Button::B, Action::Back),
+    (Button::Start, Action::Pause),
+];
+
+pub struct Gamepads {
+    subsystem: GameControllerSubsystem,
+    controllers: HashMap<u32, GameController>,
+}
+
+impl Gamepads {
+    pub fn new(subsystem: GameControllerSubsystem) -> Gamepads {
+        Gamepads {
+            subsystem,
+            controllers: HashMap::new(),
+        }
+    }
+
+    // SDL reports pads that are already connected as added at startup, so
+    // these two events cover both the initial scan and hot-plugging.
+    pub fn handle_event(&mut self, event: &Event) {
+        match event {
+            Event::ControllerDeviceAdded { which, .. } => match self.subsystem.open(*which) {
+                Ok(controller) => {
+                    self.controllers
+                        .insert(controller.instance_id(), controller);
+                }
+                Err(err) => println!("{}", err),
+            },
+            Event::ControllerDeviceRemoved { which, .. } => {
+                self.controllers.remove(which);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn is_pause(&self, button: Button) -> bool {
+        BUTTONS.contains(&(button, Action::Pause))
+    }
+
+    pub fn actions(&self) -> HashSet<Action> {
+        let mut actions = HashSet::new();
+
+        for controller in self.controllers.values() {
+            for (button, action) in BUTTONS {
+                if controller.button(button) {
+                    actions.insert(action);
+                }
+            }
+
+            let x = controller.axis(Axis::LeftX);
+            let y = controller.axis(Axis::LeftY);
+
+            if x < -STICK_DEADZONE {
+                actions.insert(Action::MoveLeft);
+            } else if x > STICK_DEADZONE {
+                actions.insert(Action::MoveRight);
+            }
+
+            if y < -STICK_DEADZONE {
+                actions.insert(Action::MenuUp);
+            } else if y > STICK_DEADZONE {
+                actions.insert(Action::MenuDown);
+            }
+        }
+
+        actions
+    }
+}
@@ -0,0 +1,74 @@
+use std::ops::RangeInclusive;
+
+use crate::rect::FRect;
+
+// A uniform grid over the playfield. Each item is filed under every cell
+// its rect touches; rects beyond the edges are clamped into the border
+// cells, so anything that overlaps still shares a cell.
+pub struct Grid<T> {
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    cells: Vec<Vec<T>>,
+}
+
+impl<T: Copy + Ord> Grid<T> {
+    pub fn new(width: f32, height: f32, cell_size: f32) -> Grid<T> {
+        let columns = ((width / cell_size).ceil() as usize).max(1);
+        let rows = ((height / cell_size).ceil() as usize).max(1);
+
+        Grid {
+            cell_size,
+            columns,
+            rows,
+            cells: (0..columns * rows).map(|_| vec![]).collect(),
+        }
+    }
+
+    // Empties every cell but keeps their storage for the next tick.
+    pub fn clear(&mut self) {
+        for cell in &mut self.cells {
+            cell.clear();
+        }
+    }
+
+    fn span(&self, start: f32, len: f32, count: usize) -> RangeInclusive<usize> {
+        let cell = |value: f32| ((value / self.cell_size).floor().max(0.0) as usize).min(count - 1);
+
+        cell(start)..=cell(start + len)
+    }
+
+    // The columns and rows of cells a rect touches.
+    fn spans(&self, rect: &FRect) -> (RangeInclusive<usize>, RangeInclusive<usize>) {
+        (
+            self.span(rect.x, rect.width(), self.columns),
+            self.span(rect.y, rect.height(), self.rows),
+        )
+    }
+
+    pub fn insert(&mut self, item: T, rect: &FRect) {
+        let (columns, rows) = self.spans(rect);
+
+        for row in rows {
+            for column in columns.clone() {
+                self.cells[row * self.columns + column].push(item);
+            }
+        }
+    }
+
+    // Fills `found` with every item sharing a cell with the rect, sorted and
+    // without repeats.
+    pub fn query(&self, rect: &FRect, found: &mut Vec<T>) {
+        let (columns, rows) = self.spans(rect);
+        found.clear();
+
+        for row in rows {
+            for column in columns.clone() {
+                found.extend_from_slice(&self.cells[row * self.columns + column]);
+            }
+        }
+
+        found.sort_unstable();
+        found.dedup();
+    }
+}
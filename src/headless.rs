@@ -0,0 +1,72 @@
+use space_invaders_rust::autopilot::autopilot;
+use space_invaders_rust::game::{State, FPS};
+use space_invaders_rust::replay::{Playback, Replay};
+use space_invaders_rust::simulation::Simulation;
+
+use crate::cli::Options;
+
+// An hour of play, so an autopilot run that never loses still ends.
+const DEFAULT_TICKS: u32 = 60 * 60 * FPS;
+
+// Runs the simulation without SDL, driven by the replay if one is given and
+// by the autopilot otherwise, then prints a summary of the final state.
+pub fn run(options: &Options) -> Result<(), String> {
+    let (mut sim, mut playback) = match &options.replay {
+        Some(path) => {
+            let replay = Replay::load(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            let playback = Playback::new(replay);
+
+            (playback.simulation(), Some(playback))
+        }
+        None => (Simulation::new(options.seed(), options.settings()), None),
+    };
+
+    let mut recording = options
+        .record
+        .as_ref()
+        .map(|_| Replay::new(sim.game.seed, sim.game.settings));
+
+    let max_ticks = options.ticks.unwrap_or(match &playback {
+        Some(playback) => playback.replay.ticks(),
+        None => DEFAULT_TICKS,
+    });
+    let mut ticks = 0;
+
+    while sim.game.state == State::Playing && ticks < max_ticks {
+        let input = match &mut playback {
+            Some(playback) => match playback.next_input() {
+                Some(input) => input,
+                None => break,
+            },
+            None => autopilot(&sim),
+        };
+
+        sim.step(&input);
+        ticks += 1;
+
+        if let Some(playback) = &playback {
+            playback.verify(&sim)?;
+        }
+
+        if let Some(recording) = &mut recording {
+            recording.record(&input, &sim);
+        }
+    }
+
+    if let (Some(recording), Some(path)) = (&recording, &options.record) {
+        recording
+            .save(path)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+
+    println!(
+        "ticks {} wave {} score {} lives {} checksum {:016x}",
+        ticks,
+        sim.game.wave,
+        sim.game.score,
+        sim.game.lives,
+        sim.checksum()
+    );
+
+    Ok(())
+}
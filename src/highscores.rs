@@ -0,0 +1,296 @@
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::util::data_dir;
+
+pub const MAX_ENTRIES: usize = 10;
+pub const INITIALS_LEN: usize = 3;
+
+const DEFAULT_DATE: &str = "1978-06-01";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighScore {
+    pub initials: String,
+    pub score: u32,
+    pub date: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighScores {
+    pub entries: Vec<HighScore>,
+}
+
+impl Default for HighScores {
+    fn default() -> Self {
+        HighScores {
+            entries: (0..MAX_ENTRIES as u32)
+                .map(|i| HighScore {
+                    initials: String::from("INV"),
+                    score: 1000 - i * 100,
+                    date: String::from(DEFAULT_DATE),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl HighScores {
+    pub fn default_path() -> Option<PathBuf> {
+        data_dir().map(|dir| dir.join("highscores.txt"))
+    }
+
+    // A file without a single entry is treated like a missing one.
+    pub fn load(path: &Path) -> HighScores {
+        match fs::read_to_string(path) {
+            Ok(contents) => match HighScores::parse(&contents) {
+                Ok(high_scores) if high_scores.entries.is_empty() => HighScores::default(),
+                Ok(high_scores) => high_scores,
+                Err(err) => {
+                    println!("{}: {}", path.display(), err);
+                    HighScores::default()
+                }
+            },
+            Err(_) => HighScores::default(),
+        }
+    }
+
+    pub fn parse(contents: &str) -> Result<HighScores, String> {
+        let mut entries = vec![];
+
+        for (i, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split_whitespace().collect();
+
+            if fields.len() != 3 {
+                return Err(format!("line {}: expected initials, score and date", i + 1));
+            }
+
+            let initials = fields[0];
+
+            if initials.len() != INITIALS_LEN || !initials.chars().all(|c| c.is_ascii_uppercase()) {
+                return Err(format!("line {}: invalid initials {:?}", i + 1, initials));
+            }
+
+            let score = fields[1]
+                .parse::<u32>()
+                .map_err(|e| format!("line {}: invalid score: {}", i + 1, e))?;
+
+            entries.push(HighScore {
+                initials: String::from(initials),
+                score,
+                date: String::from(fields[2]),
+            });
+        }
+
+        entries.sort_by_key(|entry| Reverse(entry.score));
+        entries.truncate(MAX_ENTRIES);
+
+        Ok(HighScores { entries })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+
+        let contents: String = self
+            .entries
+            .iter()
+            .map(|entry| format!("{} {} {}\n", entry.initials, entry.score, entry.date))
+            .collect();
+
+        fs::write(path, contents).map_err(|e| e.to_string())
+    }
+
+    pub fn top_score(&self) -> u32 {
+        self.entries.first().map_or(0, |entry| entry.score)
+    }
+
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.entries.len() < MAX_ENTRIES
+                || self.entries.iter().any(|entry| score > entry.score))
+    }
+
+    pub fn insert(&mut self, initials: &str, score: u32, date: &str) {
+        let position = self
+            .entries
+            .iter()
+            .position(|entry| score > entry.score)
+            .unwrap_or(self.entries.len());
+
+        self.entries.insert(
+            position,
+            HighScore {
+                initials: String::from(initials),
+                score,
+                date: String::from(date),
+            },
+        );
+
+        self.entries.truncate(MAX_ENTRIES);
+    }
+}
+
+pub fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() / 86_400) as i64;
+
+    // Civil-from-days conversion for the proleptic Gregorian calendar.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str, contents: Option<&str>) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "space-invaders-rust-{}-{}.txt",
+            name,
+            std::process::id()
+        ));
+
+        match contents {
+            Some(contents) => fs::write(&path, contents).unwrap(),
+            None => {
+                let _ = fs::remove_file(&path);
+            }
+        }
+
+        path
+    }
+
+    fn scores(high_scores: &HighScores) -> Vec<u32> {
+        high_scores
+            .entries
+            .iter()
+            .map(|entry| entry.score)
+            .collect()
+    }
+
+    #[test]
+    fn parse_reads_entries() {
+        let high_scores = HighScores::parse("ABC 120 2024-01-02\n\nXYZ 50 2024-03-04\n").unwrap();
+
+        assert_eq!(
+            high_scores.entries,
+            vec![
+                HighScore {
+                    initials: String::from("ABC"),
+                    score: 120,
+                    date: String::from("2024-01-02"),
+                },
+                HighScore {
+                    initials: String::from("XYZ"),
+                    score: 50,
+                    date: String::from("2024-03-04"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_rejects_bad_lines() {
+        for contents in [
+            "ABC 120",
+            "ABC 120 2024-01-02 extra",
+            "AB 120 2024-01-02",
+            "abc 120 2024-01-02",
+            "ABC -5 2024-01-02",
+            "ABC lots 2024-01-02",
+        ] {
+            assert!(HighScores::parse(contents).is_err(), "{:?}", contents);
+        }
+
+        assert_eq!(
+            HighScores::parse("ABC 1 2024-01-02\nbad\n").unwrap_err(),
+            "line 2: expected initials, score and date"
+        );
+    }
+
+    #[test]
+    fn parse_sorts_and_keeps_the_top_entries() {
+        let contents: String = (1..=12)
+            .map(|score| format!("AAA {} {}\n", score * 10, DEFAULT_DATE))
+            .collect();
+
+        let high_scores = HighScores::parse(&contents).unwrap();
+
+        assert_eq!(
+            scores(&high_scores),
+            vec![120, 110, 100, 90, 80, 70, 60, 50, 40, 30]
+        );
+    }
+
+    #[test]
+    fn load_falls_back_to_the_default_table() {
+        for (name, contents) in [
+            ("missing", None),
+            ("empty", Some("")),
+            ("blank", Some("  \n\t\n")),
+            ("corrupt", Some("not a high score\n")),
+        ] {
+            let path = temp_file(name, contents);
+
+            assert_eq!(HighScores::load(&path), HighScores::default(), "{}", name);
+
+            let _ = fs::remove_file(&path);
+        }
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let path = temp_file("round-trip", None);
+        let mut high_scores = HighScores::default();
+        high_scores.insert("ZZZ", 5000, "2024-05-06");
+
+        high_scores.save(&path).unwrap();
+        assert_eq!(HighScores::load(&path), high_scores);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn insert_keeps_the_table_ordered() {
+        let mut high_scores = HighScores::default();
+
+        high_scores.insert("NEW", 550, "2024-01-01");
+        high_scores.insert("TIE", 1000, "2024-01-01");
+
+        assert_eq!(
+            scores(&high_scores),
+            vec![1000, 1000, 900, 800, 700, 600, 550, 500, 400, 300]
+        );
+        assert_eq!(high_scores.entries[0].initials, "INV");
+        assert_eq!(high_scores.entries[1].initials, "TIE");
+        assert_eq!(high_scores.entries[6].initials, "NEW");
+    }
+
+    #[test]
+    fn qualifies_only_above_the_lowest_entry() {
+        let high_scores = HighScores::default();
+
+        assert!(high_scores.qualifies(101));
+        assert!(!high_scores.qualifies(100));
+        assert!(!high_scores.qualifies(0));
+        assert!(HighScores { entries: vec![] }.qualifies(1));
+    }
+}
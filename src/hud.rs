@@ -0,0 +1,161 @@
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, TextureCreator};
+use sdl2::ttf::Sdl2TtfContext;
+use sdl2::video::{Window, WindowContext};
+
+use space_invaders_rust::game::{Game, PIXEL_SIZE};
+use space_invaders_rust::layout::Layout;
+
+use crate::sprite_batch::{Layer, SpriteBatch};
+use crate::text::TextCache;
+
+const TEXT_Y: i32 = 8;
+const TEXT_GAP: i32 = 4 * PIXEL_SIZE;
+const MAX_LIFE_ICONS: u32 = 5;
+const LIFE_ICON_STEP: i32 = 10 * PIXEL_SIZE;
+
+// Where the HUD pieces go, worked out from the current text widths.
+struct Placement {
+    hi_score_x: i32,
+    wave_x: i32,
+    lives_x: i32,
+    credit_x: i32,
+    bottom_y: i32,
+}
+
+pub struct Hud<'ttf, 'a> {
+    text: TextCache<'ttf, 'a>,
+}
+
+impl<'ttf, 'a> Hud<'ttf, 'a> {
+    pub fn new(
+        ttf_context: &'ttf Sdl2TtfContext,
+        texture_creator: &'a TextureCreator<WindowContext>,
+    ) -> Result<Hud<'ttf, 'a>, String> {
+        let mut text = TextCache::new(ttf_context, texture_creator, (4 * PIXEL_SIZE) as u16)?;
+
+        text.set("score_label", "SCORE<1>")?;
+        text.set("hi_score_label", "HI-SCORE")?;
+        text.set("wave_label", "WAVE")?;
+        text.set("credit", "CREDIT 00")?;
+
+        Ok(Hud { text })
+    }
+
+    fn draw_text(&self, canvas: &mut Canvas<Window>, key: &str, x: i32, y: i32) -> i32 {
+        self.text.draw(canvas, key, x, y) + TEXT_GAP
+    }
+
+    fn place(&self, layout: &Layout) -> Placement {
+        let score_end = layout.left_edge
+            + self.text.width("score_label")
+            + TEXT_GAP
+            + self.text.width("score")
+            + TEXT_GAP;
+
+        let hi_score_width =
+            self.text.width("hi_score_label") + TEXT_GAP + self.text.width("hi_score");
+        let wave_width = self.text.width("wave_label") + TEXT_GAP + self.text.width("wave");
+        let credit_x = layout.width - layout.left_edge - self.text.width("credit");
+
+        // Portrait layouts follow the arcade: the hi-score is centred on top
+        // and lives, the wave and the credit sit below the ground line.
+        // Landscape puts the scores on the left of the top line and the rest
+        // on its right, with room kept for every life icon.
+        let (hi_score_x, wave_x, lives_x, bottom_y) = if layout.height > layout.width {
+            (
+                (layout.width - hi_score_width) / 2,
+                (layout.width - wave_width) / 2,
+                layout.left_edge,
+                layout.ground_y + PIXEL_SIZE,
+            )
+        } else {
+            let lives_width =
+                self.text.width("lives") + TEXT_GAP + MAX_LIFE_ICONS as i32 * LIFE_ICON_STEP;
+            let lives_x = credit_x - 2 * TEXT_GAP - lives_width;
+
+            (
+                score_end + TEXT_GAP,
+                lives_x - 2 * TEXT_GAP - wave_width,
+                lives_x,
+                TEXT_Y,
+            )
+        };
+
+        Placement {
+            hi_score_x,
+            wave_x,
+            lives_x,
+            credit_x,
+            bottom_y,
+        }
+    }
+
+    // Sets the texts and queues the life icons; the text itself is drawn by
+    // `draw` once the batch has been flushed, so sprites can't cover it.
+    pub fn update(
+        &mut self,
+        game: &Game,
+        hi_score: u32,
+        batch: &mut SpriteBatch,
+    ) -> Result<(), String> {
+        self.text.set("score", &format!("{:04}", game.score))?;
+        self.text
+            .set("hi_score", &format!("{:04}", hi_score.max(game.score)))?;
+        self.text.set("wave", &game.wave.to_string())?;
+        self.text.set("lives", &game.lives.to_string())?;
+
+        let placement = self.place(&game.layout);
+        let mut x = placement.lives_x + self.text.width("lives") + TEXT_GAP;
+
+        for _ in 0..game.lives.saturating_sub(1).min(MAX_LIFE_ICONS) {
+            batch.push_rect(
+                Layer::Hud,
+                "player_texture",
+                Rect::new(
+                    x,
+                    placement.bottom_y,
+                    15 * PIXEL_SIZE as u32 / 2,
+                    4 * PIXEL_SIZE as u32,
+                ),
+            );
+
+            x += LIFE_ICON_STEP;
+        }
+
+        Ok(())
+    }
+
+    pub fn draw(
+        &self,
+        canvas: &mut Canvas<Window>,
+        layout: &Layout,
+        ground_color: Color,
+    ) -> Result<(), String> {
+        let placement = self.place(layout);
+        let bottom_y = placement.bottom_y;
+
+        let x = self.draw_text(canvas, "score_label", layout.left_edge, TEXT_Y);
+        self.draw_text(canvas, "score", x, TEXT_Y);
+
+        let x = self.draw_text(canvas, "hi_score_label", placement.hi_score_x, TEXT_Y);
+        self.draw_text(canvas, "hi_score", x, TEXT_Y);
+
+        let x = self.draw_text(canvas, "wave_label", placement.wave_x, bottom_y);
+        self.draw_text(canvas, "wave", x, bottom_y);
+
+        self.draw_text(canvas, "lives", placement.lives_x, bottom_y);
+        self.draw_text(canvas, "credit", placement.credit_x, bottom_y);
+
+        canvas.set_draw_color(ground_color);
+        canvas.fill_rect(Rect::new(
+            0,
+            layout.ground_y,
+            layout.width as u32,
+            PIXEL_SIZE as u32 / 2,
+        ))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::HashSet;
+
+use sdl2::keyboard::Keycode;
+
+use space_invaders_rust::highscores::INITIALS_LEN;
+
+use crate::bindings::{Action, Bindings};
+
+pub struct InitialsEntry {
+    pub letters: [u8; INITIALS_LEN],
+    pub cursor: usize,
+    pub score: u32,
+    prev_actions: HashSet<Action>,
+    prev_keys: HashSet<Keycode>,
+}
+
+impl InitialsEntry {
+    pub fn new(score: u32, actions: &HashSet<Action>, keys: &HashSet<Keycode>) -> InitialsEntry {
+        InitialsEntry {
+            letters: [b'A'; INITIALS_LEN],
+            cursor: 0,
+            score,
+            prev_actions: actions.clone(),
+            prev_keys: keys.clone(),
+        }
+    }
+
+    pub fn initials(&self) -> String {
+        self.letters.iter().map(|letter| *letter as char).collect()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.cursor >= INITIALS_LEN
+    }
+
+    fn cycle_letter(&mut self, forward: bool) {
+        let letter = &mut self.letters[self.cursor];
+
+        *letter = match (forward, *letter) {
+            (true, b'Z') => b'A',
+            (false, b'A') => b'Z',
+            (true, _) => *letter + 1,
+            (false, _) => *letter - 1,
+        };
+    }
+
+    pub fn update(
+        &mut self,
+        bindings: &Bindings,
+        actions: &HashSet<Action>,
+        keys: &HashSet<Keycode>,
+    ) {
+        let new_actions = actions - &self.prev_actions;
+        let new_keys = keys - &self.prev_keys;
+        self.prev_actions = actions.clone();
+        self.prev_keys = keys.clone();
+
+        for action in new_actions {
+            if self.is_done() {
+                return;
+            }
+
+            match action {
+                Action::MenuUp => self.cycle_letter(true),
+                Action::MenuDown => self.cycle_letter(false),
+                Action::Confirm => self.cursor += 1,
+                Action::Back => self.cursor = self.cursor.saturating_sub(1),
+                _ => (),
+            }
+        }
+
+        // Letters can be typed directly unless they drive the menu.
+        for key in new_keys {
+            if self.is_done() {
+                return;
+            }
+
+            if bindings.is_menu_key(key) {
+                continue;
+            }
+
+            let name = key.name();
+
+            if name.len() == 1 && name.as_bytes()[0].is_ascii_uppercase() {
+                self.letters[self.cursor] = name.as_bytes()[0];
+                self.cursor += 1;
+            }
+        }
+    }
+}
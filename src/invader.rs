@@ -28,6 +28,14 @@ impl Invader {
         }
     }
 
+    pub fn points(&self) -> u32 {
+        match self.row {
+            4 => 30,
+            2 | 3 => 20,
+            _ => 10,
+        }
+    }
+
     pub fn move_x_right(&mut self) {
         self.game_object.rect.x += 10.0;
         self.toggle_anim_step();
@@ -0,0 +1,188 @@
+use crate::game::PIXEL_SIZE;
+
+pub const BARRIER_COUNT: usize = 4;
+
+// How far below the first wave each wave's formation starts, out of the
+// largest drop. Past the table the arcade cycles back to the second entry.
+const WAVE_START_DROPS: [i32; 9] = [0, 24, 40, 48, 48, 48, 56, 56, 56];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutMode {
+    Landscape,
+    Arcade,
+}
+
+// Where the first invader of a formation row sits and how far apart the
+// invaders in that row are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormationRow {
+    pub x: i32,
+    pub step: i32,
+}
+
+// A strip of the screen tinted like the cellophane on the arcade monitor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OverlayBand {
+    pub top: i32,
+    pub bottom: i32,
+    pub color: (u8, u8, u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    pub mode: LayoutMode,
+    pub width: i32,
+    pub height: i32,
+    pub left_edge: i32,
+    pub right_edge: i32,
+    pub formation_y: i32,
+    pub row_spacing: i32,
+    // Top row first.
+    pub formation_rows: [FormationRow; 5],
+    pub barrier_xs: [i32; BARRIER_COUNT],
+    pub barrier_y: i32,
+    pub ufo_y: i32,
+    pub player_y: i32,
+    pub ground_y: i32,
+    pub overlay: &'static [OverlayBand],
+}
+
+const LANDSCAPE_WIDTH: i32 = 1920;
+const LANDSCAPE_HEIGHT: i32 = 1080;
+// The same strips as the arcade, stretched to the landscape field: red over
+// the UFO's row, green over the barriers, the player and the ground.
+const LANDSCAPE_OVERLAY: [OverlayBand; 2] = [
+    OverlayBand {
+        top: 6 * PIXEL_SIZE,
+        bottom: 15 * PIXEL_SIZE,
+        color: (255, 32, 32),
+    },
+    OverlayBand {
+        top: LANDSCAPE_HEIGHT - LANDSCAPE_HEIGHT / 4 - 2 * PIXEL_SIZE,
+        bottom: LANDSCAPE_HEIGHT,
+        color: (32, 255, 32),
+    },
+];
+
+// Positions on the original 224x256 screen, in arcade pixels.
+const ARCADE_WIDTH: i32 = 224;
+const ARCADE_HEIGHT: i32 = 256;
+const ARCADE_OVERLAY: [OverlayBand; 2] = [
+    OverlayBand {
+        top: arcade(32),
+        bottom: arcade(64),
+        color: (255, 32, 32),
+    },
+    OverlayBand {
+        top: arcade(184),
+        bottom: arcade(ARCADE_HEIGHT),
+        color: (32, 255, 32),
+    },
+];
+
+const fn arcade(x: i32) -> i32 {
+    x * PIXEL_SIZE
+}
+
+impl Layout {
+    pub fn new(mode: LayoutMode) -> Layout {
+        match mode {
+            LayoutMode::Landscape => {
+                let width = LANDSCAPE_WIDTH;
+                let height = LANDSCAPE_HEIGHT;
+                let row = |x| FormationRow {
+                    x,
+                    step: width / 20,
+                };
+
+                Layout {
+                    mode,
+                    width,
+                    height,
+                    left_edge: width / 20,
+                    right_edge: width - width / 20 - 12 * PIXEL_SIZE,
+                    formation_y: height / 6,
+                    row_spacing: width / 20,
+                    formation_rows: [
+                        row(width / 4),
+                        row(width / 4),
+                        row(width / 4 - width / 240),
+                        row(width / 4 - width / 240),
+                        row(width / 4 - width / 240),
+                    ],
+                    barrier_xs: [
+                        width / 12,
+                        width / 12 + width / 4,
+                        width / 12 + width / 2,
+                        width / 12 + width * 3 / 4,
+                    ],
+                    barrier_y: height - height / 4,
+                    ufo_y: 7 * PIXEL_SIZE,
+                    player_y: height - height / 18,
+                    ground_y: height - 2 * PIXEL_SIZE,
+                    overlay: &LANDSCAPE_OVERLAY,
+                }
+            }
+            // The formation sits in 16 pixel cells with each sprite centred
+            // in its cell, as in the arcade's sprite tables.
+            LayoutMode::Arcade => {
+                let row = |x| FormationRow {
+                    x: arcade(x),
+                    step: arcade(16),
+                };
+
+                Layout {
+                    mode,
+                    width: arcade(ARCADE_WIDTH),
+                    height: arcade(ARCADE_HEIGHT),
+                    left_edge: arcade(8),
+                    right_edge: arcade(ARCADE_WIDTH - 8 - 12),
+                    formation_y: arcade(64),
+                    row_spacing: arcade(16),
+                    formation_rows: [row(28), row(26), row(26), row(26), row(26)],
+                    barrier_xs: [arcade(31), arcade(76), arcade(121), arcade(166)],
+                    barrier_y: arcade(190),
+                    ufo_y: arcade(40),
+                    player_y: arcade(216),
+                    ground_y: arcade(239),
+                    overlay: &ARCADE_OVERLAY,
+                }
+            }
+        }
+    }
+
+    // The bottom edge of the first wave's formation.
+    pub fn formation_bottom(&self) -> i32 {
+        self.formation_y
+            + (self.formation_rows.len() as i32 - 1) * self.row_spacing
+            + 8 * PIXEL_SIZE
+    }
+
+    // The largest drop leaves one invader step between the formation and
+    // the barriers, whatever the field's proportions.
+    pub fn wave_start_offset(&self, wave: u32) -> i32 {
+        let len = WAVE_START_DROPS.len() as u32;
+
+        let index = if wave <= len {
+            wave.saturating_sub(1)
+        } else {
+            (wave - 2) % (len - 1) + 1
+        };
+
+        let room = self.barrier_y - 8 * PIXEL_SIZE - self.formation_bottom();
+
+        room * WAVE_START_DROPS[index as usize] / WAVE_START_DROPS[len as usize - 1]
+    }
+
+    // The baseline the player's ship stands on; an invader reaching it has
+    // landed.
+    pub fn invasion_line(&self) -> i32 {
+        self.player_y + 8 * PIXEL_SIZE
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout::new(LayoutMode::Landscape)
+    }
+}
@@ -0,0 +1,21 @@
+pub mod autopilot;
+pub mod barrier;
+pub mod collision;
+pub mod config;
+pub mod game;
+pub mod grid;
+pub mod highscores;
+pub mod input;
+pub mod invader;
+pub mod layout;
+pub mod mask;
+pub mod player;
+pub mod rect;
+pub mod replay;
+pub mod settings;
+pub mod simulation;
+pub mod sprite_sheet;
+pub mod texture_templates;
+pub mod timer;
+pub mod ufo;
+pub mod util;
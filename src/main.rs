@@ -1,56 +1,200 @@
 extern crate sdl2;
 
-use std::time::Duration;
+use std::env;
+use std::path::PathBuf;
+use std::process;
+use std::time::{Duration, Instant};
 
 use sdl2::event::Event;
+use sdl2::image::InitFlag;
 use sdl2::keyboard::Keycode;
 use sdl2::pixels::Color;
 use sdl2::render::TextureCreator;
 
-mod barrier;
-mod collision;
-mod game;
-mod invader;
-mod player;
+mod atlas;
+mod audio;
+mod bindings;
+mod cli;
+mod draw;
+mod gamepad;
+mod headless;
+mod hud;
+mod initials;
 mod renderer;
 mod sdl_common;
-mod texture_templates;
+mod settings_menu;
+mod sprite_batch;
+mod synth;
+mod text;
 mod textures;
-mod timer;
-mod ufo;
+mod theme;
+mod title;
 mod ui;
-mod util;
 
-use crate::game::{Game, GameObject, State, FPS, PIXEL_SIZE};
-use crate::player::Player;
-use crate::sdl_common::{ContinueEvent, RetryEvent};
-use crate::textures::textures;
-use crate::timer::Timer;
+use space_invaders_rust::config::Config;
+use space_invaders_rust::game::{State, FPS, PIXEL_SIZE};
+use space_invaders_rust::highscores::{today, HighScores};
+use space_invaders_rust::layout::Layout;
+use space_invaders_rust::replay::{Playback, Replay};
+use space_invaders_rust::settings::{CollisionMode, Settings};
+use space_invaders_rust::simulation::Simulation;
+use space_invaders_rust::sprite_sheet::{SpriteManifest, DEFAULT_MANIFEST_PATH};
+
+use crate::atlas::Atlas;
+use crate::audio::Audio;
+use crate::bindings::{Action, Bindings};
+use crate::cli::{Command, Options, USAGE};
+use crate::gamepad::Gamepads;
+use crate::hud::Hud;
+use crate::initials::InitialsEntry;
+use crate::sdl_common::{get_input, ContinueEvent, RetryEvent};
+use crate::settings_menu::SettingsMenu;
+use crate::sprite_batch::SpriteBatch;
+use crate::text::TextCache;
+use crate::textures::{load_sprite_sheets, textures};
+use crate::theme::Theme;
+use crate::title::{Title, TitleAction};
 use crate::ui::{create_ui, UI};
 
+const TIMESTEP: Duration = Duration::from_nanos(1_000_000_000 / FPS as u64);
+const MAX_FRAME_TIME: Duration = Duration::from_millis(250);
+const FAST_FORWARD_STEPS: u32 = 8;
+
+fn new_game(options: &Options) -> (Simulation, Option<Replay>) {
+    let seed = options.seed();
+    let settings = options.settings();
+    let recording = if options.record.is_some() {
+        Some(Replay::new(seed, settings))
+    } else {
+        None
+    };
+
+    (Simulation::new(seed, settings), recording)
+}
+
+fn save_recording(recording: &Option<Replay>, path: &Option<PathBuf>) {
+    if let (Some(recording), Some(path)) = (recording, path) {
+        if let Err(err) = recording.save(path) {
+            println!("{}: {}", path.display(), err);
+        }
+    }
+}
+
+fn load_collision(config: &Config) -> Option<CollisionMode> {
+    let value = config.get("collision")?;
+    let mode = CollisionMode::parse(value);
+
+    if mode.is_none() {
+        println!("collision: unknown mode '{}'", value);
+    }
+
+    mode
+}
+
 fn main() -> Result<(), String> {
-    let (mut canvas, event, mut event_pump) = sdl_common::init()?;
+    let mut options = match cli::parse(env::args().skip(1)) {
+        Ok(Command::Run(options)) => options,
+        Ok(Command::Help) => {
+            print!("{}", USAGE);
+            return Ok(());
+        }
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            process::exit(2);
+        }
+    };
+
+    let config_path = options.config.clone().or_else(Config::default_path);
+    let mut config = match &config_path {
+        Some(path) => Config::load(path),
+        None => Config::default(),
+    };
+
+    if options.collision.is_none() {
+        options.collision = load_collision(&config);
+    }
+
+    if options.headless {
+        return headless::run(&options);
+    }
+
+    let mut playback = match &options.replay {
+        Some(path) => Some(Playback::new(
+            Replay::load(path).map_err(|e| format!("{}: {}", path.display(), e))?,
+        )),
+        None => None,
+    };
+
+    let settings = match &playback {
+        Some(playback) => playback.replay.settings,
+        None => options.settings(),
+    };
+
+    let (mut canvas, event, mut event_pump, audio_subsystem, game_controller) =
+        sdl_common::init(&options, &Layout::new(settings.layout))?;
+    let mut audio = Audio::new(audio_subsystem);
+    audio.set_muted(options.mute);
+    let mut gamepads = Gamepads::new(game_controller);
 
     canvas.set_draw_color(Color::RGB(0, 0, 0));
     canvas.clear();
     canvas.present();
 
-    let mut game = Game::new();
-    let mut player = Player::new();
-    let mut timer = Timer::new(&game);
+    let mut recording: Option<Replay> = None;
+    let mut sim = match &playback {
+        Some(playback) => playback.simulation(),
+        None => {
+            let mut sim = Simulation::new(options.seed(), options.settings());
+            sim.game.set_title();
+            sim
+        }
+    };
+    // A replayed game keeps counting as a replay after control is handed
+    // back, so its score never reaches the high score table.
+    let mut replaying = playback.is_some();
+    let mut desynced = false;
+
+    let mut title = Title::new(settings);
+    let mut settings_menu: Option<SettingsMenu> = None;
+
+    let mut bindings = Bindings::load(&config);
+    let mut theme = Theme::load(&config);
 
+    let ttf_context = sdl2::ttf::init().map_err(|e| e.to_string())?;
+    let _image_context = sdl2::image::init(InitFlag::PNG)?;
     let texture_creator: TextureCreator<_> = canvas.texture_creator();
-    let (textures, missing_texture, empty_texture) = textures(&mut canvas, &texture_creator)?;
+    let (mut textures, missing_texture, empty_texture) = textures(&mut canvas, &texture_creator)?;
+
+    let sprites_path = options
+        .sprites
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_MANIFEST_PATH));
+
+    match SpriteManifest::load(&sprites_path) {
+        Ok(Some(manifest)) => load_sprite_sheets(&texture_creator, &manifest, &mut textures),
+        Ok(None) if options.sprites.is_some() => {
+            println!("{}: file not found", sprites_path.display())
+        }
+        Ok(None) => (),
+        Err(err) => println!("{}", err),
+    }
+
+    let mut atlas = Atlas::new(&mut canvas, &texture_creator, textures, missing_texture)?;
     let (modal_hash, arrow_texture, ui_texture_hash, ui_targets_hash, default_target) =
-        create_ui(&mut canvas, &texture_creator)?;
-
-    let explosion_game_object = GameObject::new(
-        player.game_object.rect.x,
-        player.game_object.rect.y,
-        12 * PIXEL_SIZE as u32,
-        10 * PIXEL_SIZE as u32,
-        String::from("explosion_texture"),
-    );
+        create_ui(&mut canvas, &texture_creator, &ttf_context)?;
+    let mut hud = Hud::new(&ttf_context, &texture_creator)?;
+    let mut logo_text = TextCache::new(&ttf_context, &texture_creator, (16 * PIXEL_SIZE) as u16)?;
+    let mut menu_text = TextCache::new(&ttf_context, &texture_creator, (8 * PIXEL_SIZE) as u16)?;
+    let mut table_text = TextCache::new(&ttf_context, &texture_creator, (3 * PIXEL_SIZE) as u16)?;
+
+    let high_scores_path = HighScores::default_path();
+    let mut high_scores = match &high_scores_path {
+        Some(path) => HighScores::load(path),
+        None => HighScores::default(),
+    };
+    let mut hi_score = high_scores.top_score();
+    let mut initials_entry: Option<InitialsEntry> = None;
+    let mut score_recorded = false;
 
     let mut ui = UI::new(
         match ui_targets_hash.get("retry") {
@@ -63,14 +207,35 @@ fn main() -> Result<(), String> {
         },
     );
 
+    let mut previous_frame = Instant::now();
+    let mut accumulator = Duration::ZERO;
+
     'running: loop {
+        let now = Instant::now();
+        accumulator += (now - previous_frame).min(MAX_FRAME_TIME);
+        previous_frame = now;
+
         for event in event_pump.poll_iter() {
+            gamepads.handle_event(&event);
+
             match event {
                 Event::Quit { .. } => break 'running,
                 Event::KeyDown {
-                    keycode: Some(Keycode::Escape),
-                    ..
-                } => game.set_paused(),
+                    keycode: Some(key), ..
+                } if bindings.is_bound(Action::Pause, key) => sim.game.set_paused(),
+                Event::ControllerButtonDown { button, .. } if gamepads.is_pause(button) => {
+                    sim.game.set_paused()
+                }
+                // Mute keys type letters and get rebound while those
+                // screens are taking raw key presses.
+                Event::KeyDown {
+                    keycode: Some(key), ..
+                } if bindings.is_bound(Action::Mute, key)
+                    && initials_entry.is_none()
+                    && !settings_menu.as_ref().is_some_and(|menu| menu.capturing) =>
+                {
+                    audio.toggle_mute()
+                }
                 _ => {}
             }
 
@@ -78,18 +243,15 @@ fn main() -> Result<(), String> {
                 let retry = event.as_user_event_type::<RetryEvent>();
                 let continue_event = event.as_user_event_type::<ContinueEvent>();
 
-                match retry {
-                    Some(_) => {
-                        game = Game::new();
-                        player = Player::new();
-                        timer = Timer::new(&game);
-                    }
-                    None => (),
+                if retry.is_some() {
+                    (sim, recording) = new_game(&options);
+                    playback = None;
+                    replaying = false;
+                    score_recorded = false;
                 }
 
-                match continue_event {
-                    Some(_) => game.set_playing(),
-                    None => (),
+                if continue_event.is_some() {
+                    sim.game.set_playing();
                 }
             }
         }
@@ -100,39 +262,211 @@ fn main() -> Result<(), String> {
             .filter_map(Keycode::from_scancode)
             .collect();
 
-        if game.state == State::Playing {
+        let actions = &bindings.actions(&keys) | &gamepads.actions();
+        let input = get_input(&actions);
+
+        let steps = if playback.is_some() && keys.contains(&Keycode::Tab) {
+            FAST_FORWARD_STEPS
+        } else {
+            1
+        };
+
+        while accumulator >= TIMESTEP {
+            for _ in 0..steps {
+                let playing = sim.game.state == State::Playing;
+
+                // Once the replay runs out the game pauses and continuing
+                // hands control over to the live input.
+                if playing && playback.as_ref().is_some_and(|p| p.is_finished()) {
+                    println!("replay finished");
+                    playback = None;
+                    sim.game.set_paused();
+                    break;
+                }
+
+                let input = match &mut playback {
+                    Some(playback) if playing => playback.next_input().unwrap_or_default(),
+                    _ => input,
+                };
+
+                sim.step(&input);
+                audio.update(&sim.game);
+
+                if playing {
+                    // Everything after the first mismatch differs too, so only
+                    // that one is worth reporting.
+                    if let Some(playback) = playback.as_ref().filter(|_| !desynced) {
+                        if let Err(err) = playback.verify(&sim) {
+                            println!("{}", err);
+                            desynced = true;
+                        }
+                    }
+
+                    if let Some(recording) = &mut recording {
+                        recording.record(&input, &sim);
+                    }
+                }
+
+                if sim.game.state == State::Title && settings_menu.is_none() {
+                    title.step();
+                }
+            }
+
+            accumulator -= TIMESTEP;
+        }
+
+        hi_score = hi_score.max(sim.game.score);
+
+        if sim.game.state.is_finished() && !score_recorded {
+            score_recorded = true;
+            save_recording(&recording, &options.record);
+
+            if !replaying && high_scores.qualifies(sim.game.score) {
+                initials_entry = Some(InitialsEntry::new(sim.game.score, &actions, &keys));
+            }
+        }
+
+        let alpha = accumulator.as_secs_f32() / TIMESTEP.as_secs_f32();
+
+        if let Some(menu) = &settings_menu {
+            if let Err(err) = renderer::update_settings(
+                &mut canvas,
+                menu,
+                &bindings,
+                theme,
+                options.settings().collision,
+                &mut menu_text,
+            ) {
+                println!("{}", err);
+            }
+        } else if sim.game.state == State::Title {
+            let result = match &title.demo {
+                Some(demo) => {
+                    renderer::update(
+                        &mut canvas,
+                        demo,
+                        &mut atlas,
+                        &mut hud,
+                        theme,
+                        hi_score,
+                        alpha,
+                    );
+                    Ok(())
+                }
+                None => renderer::update_title(
+                    &mut canvas,
+                    &mut SpriteBatch::new(&mut atlas, theme),
+                    &mut logo_text,
+                    &mut menu_text,
+                    &mut table_text,
+                    &high_scores,
+                    &title,
+                ),
+            };
+
+            if let Err(err) = result {
+                println!("{}", err);
+            }
+        } else if sim.game.state == State::Playing {
             renderer::update(
                 &mut canvas,
-                &game,
-                &player,
-                &textures,
-                &missing_texture,
-                &timer.player_explosion_timer,
-                &explosion_game_object,
+                &sim,
+                &mut atlas,
+                &mut hud,
+                theme,
+                hi_score,
+                alpha,
             );
-
-            collision::update(&mut player, &mut game);
-
-            game.update(&timer);
-            player.update(&keys, &timer);
+        } else if let Some(entry) = &initials_entry {
+            if let Err(err) = renderer::update_initials(&mut canvas, entry, &mut menu_text) {
+                println!("{}", err);
+            }
         } else {
             renderer::update_ui(
                 &mut canvas,
                 &modal_hash,
                 &arrow_texture,
                 &ui,
-                &game,
+                &sim.game,
                 &ui_targets_hash,
                 &ui_texture_hash,
                 &empty_texture,
-            )
+            );
+
+            if sim.game.state.is_finished() {
+                if let Err(err) = renderer::draw_high_scores(
+                    &mut canvas,
+                    &mut table_text,
+                    &high_scores,
+                    2 * PIXEL_SIZE,
+                    sim.game.layout.height / 4,
+                ) {
+                    println!("{}", err);
+                }
+            }
         }
 
-        ui.update(&keys, &event, &game.state);
+        canvas.present();
+
+        if let Some(menu) = &mut settings_menu {
+            let collision = options
+                .collision
+                .get_or_insert(Settings::default().collision);
+
+            if menu.update(&mut bindings, &mut theme, collision, &actions, &keys) {
+                bindings.store(&mut config);
+                theme.store(&mut config);
+                config.set("collision", collision.name());
+
+                if let Some(path) = &config_path {
+                    if let Err(err) = config.save(path) {
+                        println!("{}: {}", path.display(), err);
+                    }
+                }
+
+                settings_menu = None;
+                title.reset(&bindings.actions(&keys), &keys);
+            }
+        } else if sim.game.state == State::Title {
+            match title.update(&actions, &keys) {
+                TitleAction::Start => {
+                    (sim, recording) = new_game(&options);
+                    replaying = false;
+                    score_recorded = false;
+                }
+                TitleAction::Settings => settings_menu = Some(SettingsMenu::new(&actions, &keys)),
+                TitleAction::Quit => break 'running,
+                TitleAction::None => (),
+            }
+
+            ui.skip_actions(&actions);
+        } else {
+            match &mut initials_entry {
+                Some(entry) => {
+                    entry.update(&bindings, &actions, &keys);
+                    ui.skip_actions(&actions);
+
+                    if entry.is_done() {
+                        high_scores.insert(&entry.initials(), entry.score, &today());
+
+                        if let Some(path) = &high_scores_path {
+                            if let Err(err) = high_scores.save(path) {
+                                println!("{}: {}", path.display(), err);
+                            }
+                        }
 
-        timer.update(&game, &player);
+                        initials_entry = None;
+                    }
+                }
+                None => ui.update(&actions, &event, &sim.game.state),
+            }
+        }
+
+        ::std::thread::sleep(Duration::from_millis(1));
+    }
 
-        ::std::thread::sleep(Duration::new(0, 1_000_000_000u32 / FPS));
+    if !score_recorded {
+        save_recording(&recording, &options.record);
     }
 
     Ok(())
@@ -0,0 +1,171 @@
+use crate::rect::FRect;
+use crate::texture_templates::{
+    INVADER_1_1, INVADER_1_2, INVADER_2_1, INVADER_2_2, INVADER_3_1, INVADER_3_2, INVADER_SHOT,
+    PLAYER, UFO,
+};
+use crate::util::overlaps;
+
+// The set pixels of a sprite matrix, stretched over whatever rect the
+// sprite is drawn into.
+#[derive(Debug, Clone, Copy)]
+pub struct Mask {
+    pub width: usize,
+    pub height: usize,
+    bits: &'static [u32],
+}
+
+impl Mask {
+    pub fn new<const W: usize, const H: usize>(matrix: &'static [[u32; W]; H]) -> Mask {
+        Mask {
+            width: W,
+            height: H,
+            bits: matrix.as_flattened(),
+        }
+    }
+
+    // Whether the point, given in the same space as `rect`, lands on a set
+    // pixel.
+    pub fn is_set(&self, rect: &FRect, x: f32, y: f32) -> bool {
+        let col = ((x - rect.x) / rect.width() * self.width as f32).floor();
+        let row = ((y - rect.y) / rect.height() * self.height as f32).floor();
+
+        if col < 0.0 || row < 0.0 || col >= self.width as f32 || row >= self.height as f32 {
+            return false;
+        }
+
+        self.bits[row as usize * self.width + col as usize] != 0
+    }
+}
+
+// The mask for a sprite's current frame; sprites without one, such as the
+// player's shot, are solid.
+pub fn sprite_mask(texture_name: &str, anim_step: i32) -> Option<Mask> {
+    let first_frame = anim_step == 1;
+
+    match texture_name {
+        "invader_texture1" if first_frame => Some(Mask::new(&INVADER_1_1)),
+        "invader_texture1" => Some(Mask::new(&INVADER_1_2)),
+        "invader_texture2" if first_frame => Some(Mask::new(&INVADER_2_1)),
+        "invader_texture2" => Some(Mask::new(&INVADER_2_2)),
+        "invader_texture3" if first_frame => Some(Mask::new(&INVADER_3_1)),
+        "invader_texture3" => Some(Mask::new(&INVADER_3_2)),
+        "player_texture" => Some(Mask::new(&PLAYER)),
+        "ufo_texture" => Some(Mask::new(&UFO)),
+        "invader_shot_texture" => Some(Mask::new(&INVADER_SHOT)),
+        _ => None,
+    }
+}
+
+// Checks the rects first, then samples every canvas pixel they share for
+// one that is set in both masks.
+pub fn pixels_overlap(a: &FRect, a_mask: Option<Mask>, b: &FRect, b_mask: Option<Mask>) -> bool {
+    if !overlaps(a, b) {
+        return false;
+    }
+
+    let left = a.x.max(b.x);
+    let right = (a.x + a.width()).min(b.x + b.width());
+    let top = a.y.max(b.y);
+    let bottom = (a.y + a.height()).min(b.y + b.height());
+
+    let mut y = top.floor() + 0.5;
+
+    while y < bottom {
+        let mut x = left.floor() + 0.5;
+
+        while x < right {
+            let in_a = a_mask.is_none_or(|mask| mask.is_set(a, x, y));
+            let in_b = b_mask.is_none_or(|mask| mask.is_set(b, x, y));
+
+            if x >= left && y >= top && in_a && in_b {
+                return true;
+            }
+
+            x += 1.0;
+        }
+
+        y += 1.0;
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::PIXEL_SIZE;
+
+    const P: f32 = PIXEL_SIZE as f32;
+
+    fn invader_rect(x: f32, y: f32) -> FRect {
+        FRect::new(x, y, 11.0 * P, 8.0 * P)
+    }
+
+    #[test]
+    fn shot_between_the_legs_misses_pixels_but_hits_boxes() {
+        let invader = invader_rect(0.0, 0.0);
+        let mask = sprite_mask("invader_texture2", 1);
+        // Column 5 of the bottom two rows is the gap between the legs.
+        let shot = FRect::new(5.0 * P + 1.0, 6.0 * P, 2.0, 2.0 * P);
+
+        assert!(overlaps(&invader, &shot));
+        assert!(!pixels_overlap(&invader, mask, &shot, None));
+
+        let body = FRect::new(5.0 * P + 1.0, 5.0 * P, 2.0, 2.0 * P);
+
+        assert!(pixels_overlap(&invader, mask, &body, None));
+    }
+
+    #[test]
+    fn anim_step_picks_the_frame() {
+        let rect = FRect::new(0.0, 0.0, 11.0, 8.0);
+        let first = sprite_mask("invader_texture2", 1).unwrap();
+        let second = sprite_mask("invader_texture2", 2).unwrap();
+
+        // Only the second frame raises its arms into the top left corner.
+        assert!(!first.is_set(&rect, 0.5, 0.5));
+        assert!(second.is_set(&rect, 0.5, 0.5));
+        assert!(sprite_mask("shot_texture", 1).is_none());
+    }
+
+    #[test]
+    fn masks_follow_unaligned_rects() {
+        let invader = invader_rect(100.25, 50.75);
+        let mask = sprite_mask("invader_texture2", 1);
+
+        for (row, pixels) in INVADER_2_1.iter().enumerate() {
+            for (col, pixel) in pixels.iter().enumerate() {
+                let probe = FRect::new(
+                    invader.x + col as f32 * P + 2.0,
+                    invader.y + row as f32 * P + 2.0,
+                    2.0,
+                    2.0,
+                );
+
+                assert_eq!(
+                    pixels_overlap(&invader, mask, &probe, None),
+                    *pixel != 0,
+                    "col {} row {}",
+                    col,
+                    row
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn two_masks_only_hit_where_both_are_set() {
+        let a = invader_rect(0.0, 0.0);
+        let mask = sprite_mask("invader_texture2", 1);
+
+        // Only the empty bottom right and top left corners share space.
+        let corner = invader_rect(10.0 * P + 0.5, 7.0 * P + 0.5);
+
+        assert!(overlaps(&a, &corner));
+        assert!(!pixels_overlap(&a, mask, &corner, mask));
+
+        let body = invader_rect(5.0 * P + 0.5, 4.0 * P + 0.5);
+
+        assert!(pixels_overlap(&a, mask, &body, mask));
+    }
+}
@@ -1,29 +1,24 @@
-use std::collections::HashSet;
-
-use sdl2::keyboard::Keycode;
-
-use crate::game::{
-    GameObject, CANVAS_HEIGHT, CANVAS_LEFT_EDGE, CANVAS_RIGHT_EDGE, CANVAS_WIDTH, PIXEL_SIZE,
-};
-use crate::Timer;
-
-const HEIGHT_DIV_18: i32 = CANVAS_HEIGHT / 18;
+use crate::game::{GameObject, DELTA_TIME, PIXEL_SIZE};
+use crate::input::Input;
+use crate::layout::Layout;
 
 pub struct Player {
     pub game_object: GameObject,
     pub bullets: Vec<GameObject>,
     moving_left: bool,
     moving_right: bool,
-    prev_keys: HashSet<Keycode>,
+    prev_input: Input,
     shot_timer: u32,
+    pub shots_fired: u32,
+    layout: Layout,
 }
 
 impl Player {
-    pub fn new() -> Self {
+    pub fn new(layout: &Layout) -> Self {
         Player {
             game_object: GameObject::new(
-                CANVAS_WIDTH as f32 / 2.0,
-                (CANVAS_HEIGHT - HEIGHT_DIV_18) as f32,
+                layout.width as f32 / 2.0,
+                layout.player_y as f32,
                 15 * PIXEL_SIZE as u32,
                 8 * PIXEL_SIZE as u32,
                 String::from("player_texture"),
@@ -31,11 +26,23 @@ impl Player {
             moving_left: false,
             moving_right: false,
             bullets: vec![],
-            prev_keys: HashSet::new(),
+            prev_input: Input::default(),
             shot_timer: 1,
+            shots_fired: 0,
+            layout: *layout,
         }
     }
 
+    pub fn respawn(&mut self) {
+        let spawn = Player::new(&self.layout);
+
+        self.game_object = spawn.game_object;
+        self.bullets.clear();
+        self.moving_left = false;
+        self.moving_right = false;
+        self.prev_input = Input::default();
+    }
+
     pub fn set_moving_left(&mut self, moving: bool) {
         self.moving_left = moving;
         if moving {
@@ -51,50 +58,57 @@ impl Player {
     }
 
     pub fn shoot(&mut self) {
+        self.shots_fired += 1;
+
         self.bullets.push(GameObject::new(
             self.game_object.rect.x + (self.game_object.rect.width() / 2.0) - 3.0,
             self.game_object.rect.y - (self.game_object.rect.height() / 2.0),
-            1 * PIXEL_SIZE as u32,
+            PIXEL_SIZE as u32,
             4 * PIXEL_SIZE as u32,
             String::from("shot_texture"),
         ));
     }
 
-    pub fn update(&mut self, keys: &HashSet<Keycode>, timer: &Timer) {
+    pub fn store_prev_rects(&mut self) {
+        self.game_object.store_prev_rect();
+
+        for bullet in &mut self.bullets {
+            bullet.store_prev_rect();
+        }
+    }
+
+    pub fn update(&mut self, input: &Input) {
         if self.game_object.is_destroyed {
             return;
         }
 
-        let new_keys = keys - &self.prev_keys;
-        let old_keys = &self.prev_keys - keys;
-
-        if !new_keys.is_empty() || !old_keys.is_empty() {
-            if new_keys.contains(&Keycode::Left) {
+        if *input != self.prev_input {
+            if input.left && !self.prev_input.left {
                 self.set_moving_left(true);
-            } else if old_keys.contains(&Keycode::Left) {
+            } else if !input.left && self.prev_input.left {
                 self.set_moving_left(false);
             }
 
-            if new_keys.contains(&Keycode::Right) {
+            if input.right && !self.prev_input.right {
                 self.set_moving_right(true);
-            } else if old_keys.contains(&Keycode::Right) {
+            } else if !input.right && self.prev_input.right {
                 self.set_moving_right(false);
             }
 
-            if new_keys.contains(&Keycode::Space) && self.shot_timer == 0 {
+            if input.fire && !self.prev_input.fire && self.shot_timer == 0 {
                 self.shoot();
                 self.shot_timer = 20;
             }
         }
 
-        self.prev_keys = keys.clone();
+        self.prev_input = *input;
 
-        if self.moving_left && self.game_object.rect.x > CANVAS_LEFT_EDGE as f32 {
-            self.game_object.rect.x -= 0.6 * timer.delta_time as f32;
+        if self.moving_left && self.game_object.rect.x > self.layout.left_edge as f32 {
+            self.game_object.rect.x -= 0.6 * DELTA_TIME;
         }
 
-        if self.moving_right && self.game_object.rect.x < CANVAS_RIGHT_EDGE as f32 {
-            self.game_object.rect.x += 0.6 * timer.delta_time as f32;
+        if self.moving_right && self.game_object.rect.x < self.layout.right_edge as f32 {
+            self.game_object.rect.x += 0.6 * DELTA_TIME;
         }
 
         if !self.bullets.is_empty() {
@@ -111,3 +125,9 @@ impl Player {
         }
     }
 }
+
+impl Default for Player {
+    fn default() -> Self {
+        Self::new(&Layout::default())
+    }
+}
@@ -0,0 +1,53 @@
+use std::hash::{Hash, Hasher};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FRect {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+impl FRect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        FRect {
+            x,
+            y,
+            w: width,
+            h: height,
+        }
+    }
+
+    pub fn x(&self) -> f32 {
+        self.x
+    }
+
+    pub fn y(&self) -> f32 {
+        self.y
+    }
+
+    pub fn width(&self) -> f32 {
+        self.w
+    }
+
+    pub fn height(&self) -> f32 {
+        self.h
+    }
+
+    pub fn lerp(&self, to: &FRect, alpha: f32) -> FRect {
+        FRect::new(
+            self.x + (to.x - self.x) * alpha,
+            self.y + (to.y - self.y) * alpha,
+            to.w,
+            to.h,
+        )
+    }
+}
+
+impl Hash for FRect {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for value in [self.x, self.y, self.w, self.h] {
+            value.to_bits().hash(state);
+        }
+    }
+}
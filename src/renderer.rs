@@ -1,88 +1,113 @@
 use std::collections::HashMap;
 
 use sdl2::pixels::Color;
-use sdl2::rect::{FRect, Rect};
+use sdl2::rect::Rect;
 use sdl2::render::{Canvas, Texture};
 use sdl2::video::Window;
 
-use crate::game::{Game, GameObject, PIXEL_SIZE};
-use crate::player::Player;
+use space_invaders_rust::game::{Game, GameObject, PIXEL_SIZE};
+use space_invaders_rust::highscores::HighScores;
+use space_invaders_rust::settings::CollisionMode;
+use space_invaders_rust::simulation::Simulation;
+use space_invaders_rust::texture_templates::{INVADER_1_1, INVADER_2_1, INVADER_3_1, UFO};
+
+use crate::atlas::{barrier_slot, Atlas};
+use crate::bindings::{Bindings, ACTIONS};
+use crate::draw::{canvas_size, draw_overlay, draw_texture_nameless_rect, to_sdl_rect};
+use crate::hud::Hud;
+use crate::initials::InitialsEntry;
+use crate::settings_menu::{SettingsMenu, BACK_ROW, COLLISION_ROW, RESET_ROW, THEME_ROW};
+use crate::sprite_batch::{Layer, SpriteBatch};
+use crate::text::TextCache;
+use crate::theme::Theme;
+use crate::title::{Title, MENU_ITEMS};
 use crate::ui::UI;
-use crate::util::{
-    draw_anim_texture, draw_texture, draw_texture_nameless, draw_texture_nameless_rect,
-};
 
 pub fn update<'a>(
     canvas: &mut Canvas<Window>,
-    game: &Game,
-    player: &Player,
-    textures: &HashMap<String, Texture<'a>>,
-    missing_texture: &Texture<'a>,
-    player_explosion_timer: &i32,
-    explosion_game_object: &GameObject,
+    sim: &Simulation,
+    atlas: &mut Atlas<'a>,
+    hud: &mut Hud<'_, 'a>,
+    theme: &Theme,
+    hi_score: u32,
+    alpha: f32,
 ) {
+    let game = &sim.game;
+    let player = &sim.player;
+
     canvas.set_draw_color(Color::RGB(0, 0, 0));
     canvas.clear();
 
+    if let Err(err) = atlas.sync_barriers(canvas, &game.barrier_row) {
+        println!("{}", err);
+    }
+
+    let mut batch = SpriteBatch::new(atlas, theme);
+
     for invader in &game.invaders {
-        draw_anim_texture(
-            canvas,
-            textures,
-            missing_texture,
-            &invader.game_object,
-            &invader.anim_step,
-        );
+        batch.push_anim(Layer::Invaders, &invader.game_object, invader.anim_step);
     }
 
     for shot in &game.invader_shots {
-        draw_texture(canvas, textures, missing_texture, &shot);
+        batch.push_object(Layer::InvaderShots, shot, alpha);
     }
 
     for explosion in &game.explosions {
-        draw_texture(canvas, textures, missing_texture, &explosion.0);
-    }
-
-    for barrier in &game.barrier_row {
-        draw_texture(canvas, textures, missing_texture, &barrier.game_object);
-
-        for collider in &barrier.colliders {
-            if collider.is_destroyed {
-                draw_texture_nameless(
-                    canvas,
-                    match textures.get("barrier_mask_texture") {
-                        Some(tex) => tex,
-                        None => &missing_texture,
-                    },
-                    &FRect::new(
-                        collider.rect.x,
-                        collider.rect.y,
-                        5.0 * PIXEL_SIZE as f32,
-                        5.0 * PIXEL_SIZE as f32,
-                    ),
-                );
-            }
-        }
+        batch.push_object(Layer::Explosions, &explosion.0, alpha);
+    }
+
+    for (i, barrier) in game.barrier_row.iter().enumerate() {
+        batch.push(
+            Layer::Barriers,
+            &barrier_slot(i),
+            to_sdl_rect(&barrier.game_object.interpolated_rect(alpha)),
+        );
     }
 
     if !player.game_object.is_destroyed {
-        draw_texture(canvas, textures, missing_texture, &player.game_object);
+        batch.push_object(Layer::Player, &player.game_object, alpha);
     }
 
-    if *player_explosion_timer > 0 && *player_explosion_timer < 10 {
-        draw_texture(canvas, textures, missing_texture, &explosion_game_object);
+    if sim.timer.player_explosion_timer > 0 && sim.timer.player_explosion_timer < 10 {
+        let explosion_game_object = GameObject::new(
+            player.game_object.rect.x,
+            player.game_object.rect.y,
+            12 * PIXEL_SIZE as u32,
+            10 * PIXEL_SIZE as u32,
+            String::from("explosion_texture"),
+        );
+
+        batch.push_object(Layer::Explosions, &explosion_game_object, alpha);
     }
 
     if game.ufo_active {
-        draw_texture(canvas, textures, missing_texture, &game.ufo.game_object);
+        batch.push_object(Layer::Ufo, &game.ufo.game_object, alpha);
     }
 
     for bullet in &player.bullets {
-        draw_texture(canvas, textures, missing_texture, &bullet);
+        batch.push_object(Layer::Bullets, bullet, alpha);
+    }
+
+    if let Err(err) = hud.update(game, hi_score, &mut batch) {
+        println!("{}", err);
+    }
+
+    if let Err(err) = batch.draw(canvas) {
+        println!("{}", err);
     }
 
-    canvas.present();
+    if let Err(err) = hud.draw(canvas, &game.layout, theme.ground) {
+        println!("{}", err);
+    }
+
+    if theme.overlay {
+        if let Err(err) = draw_overlay(canvas, &game.layout) {
+            println!("{}", err);
+        }
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_ui<'a>(
     canvas: &mut Canvas<Window>,
     modal_hash: &HashMap<Rect, Texture<'a>>,
@@ -109,11 +134,291 @@ pub fn update_ui<'a>(
             canvas,
             match ui_texture_hash.get(&ui_el.0) {
                 Some(texture) => texture,
-                None => &empty_texture,
+                None => empty_texture,
             },
             &ui_el.1,
         );
     }
+}
+
+pub fn update_initials(
+    canvas: &mut Canvas<Window>,
+    entry: &InitialsEntry,
+    text: &mut TextCache,
+) -> Result<(), String> {
+    canvas.set_draw_color(Color::RGB(0, 0, 0));
+    canvas.clear();
+
+    let (width, height) = canvas_size(canvas);
+
+    text.set("new_high_score", "NEW HIGH SCORE")?;
+    text.set("initials_score", &format!("{:04}", entry.score))?;
+    text.set("enter_initials", "ENTER YOUR INITIALS")?;
+
+    for (i, key) in ["new_high_score", "initials_score", "enter_initials"]
+        .iter()
+        .enumerate()
+    {
+        let x = (width - text.width(key)) / 2;
+        text.draw(canvas, key, x, height / 6 + i as i32 * 20 * PIXEL_SIZE);
+    }
+
+    let letter_width = 16 * PIXEL_SIZE;
+    let letters_x = (width - letter_width * entry.letters.len() as i32) / 2;
+    let letters_y = height * 2 / 3;
+
+    for (i, letter) in entry.letters.iter().enumerate() {
+        let key = format!("initial_{}", i);
+        let x = letters_x + i as i32 * letter_width;
+
+        text.set(&key, &(*letter as char).to_string())?;
+        text.draw(canvas, &key, x, letters_y);
+
+        if i == entry.cursor {
+            canvas.set_draw_color(Color::RGB(50, 255, 0));
+            canvas.fill_rect(Rect::new(
+                x,
+                letters_y + 10 * PIXEL_SIZE,
+                text.width(&key) as u32,
+                PIXEL_SIZE as u32,
+            ))?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn draw_high_scores(
+    canvas: &mut Canvas<Window>,
+    text: &mut TextCache,
+    high_scores: &HighScores,
+    x: i32,
+    y: i32,
+) -> Result<(), String> {
+    let row_height = 6 * PIXEL_SIZE;
+
+    text.set("high_scores", "HIGH SCORES")?;
+    text.draw(canvas, "high_scores", x, y);
+
+    for (i, entry) in high_scores.entries.iter().enumerate() {
+        let key = format!("high_score_{}", i);
+
+        text.set(
+            &key,
+            &format!(
+                "{:>2} {} {:>6} {}",
+                i + 1,
+                entry.initials,
+                entry.score,
+                entry.date
+            ),
+        )?;
+        text.draw(canvas, &key, x, y + (i as i32 + 2) * row_height);
+    }
+
+    Ok(())
+}
+
+pub fn update_title(
+    canvas: &mut Canvas<Window>,
+    batch: &mut SpriteBatch,
+    logo_text: &mut TextCache,
+    text: &mut TextCache,
+    table_text: &mut TextCache,
+    high_scores: &HighScores,
+    title: &Title,
+) -> Result<(), String> {
+    canvas.set_draw_color(Color::RGB(0, 0, 0));
+    canvas.clear();
+
+    let (width, height) = canvas_size(canvas);
+
+    logo_text.set("logo", "SPACE INVADERS")?;
+    logo_text.draw(
+        canvas,
+        "logo",
+        (width - logo_text.width("logo")) / 2,
+        height / 12,
+    );
+
+    let advance_table = [
+        ("ufo_texture", UFO[0].len(), UFO.len(), "= ? MYSTERY"),
+        (
+            "invader_texture1_1",
+            INVADER_1_1[0].len(),
+            INVADER_1_1.len(),
+            "= 30 POINTS",
+        ),
+        (
+            "invader_texture2_1",
+            INVADER_2_1[0].len(),
+            INVADER_2_1.len(),
+            "= 20 POINTS",
+        ),
+        (
+            "invader_texture3_1",
+            INVADER_3_1[0].len(),
+            INVADER_3_1.len(),
+            "= 10 POINTS",
+        ),
+    ];
+
+    let table_x = width / 10;
+    let table_y = height / 4;
+    let row_height = 16 * PIXEL_SIZE;
+    let slot_width = 16 * PIXEL_SIZE;
+
+    text.set("score_advance", "*SCORE ADVANCE TABLE*")?;
+    text.draw(canvas, "score_advance", table_x, table_y);
+
+    for (i, (texture_name, width, height, label)) in advance_table.iter().enumerate() {
+        let y = table_y + (i as i32 + 1) * row_height;
+        let sprite_width = *width as i32 * PIXEL_SIZE;
+
+        batch.push_rect(
+            Layer::Hud,
+            texture_name,
+            Rect::new(
+                table_x + (slot_width - sprite_width) / 2,
+                y,
+                sprite_width as u32,
+                (*height as i32 * PIXEL_SIZE) as u32,
+            ),
+        );
+
+        let key = format!("advance_{}", i);
+        text.set(&key, label)?;
+        text.draw(canvas, &key, table_x + slot_width + 4 * PIXEL_SIZE, y);
+    }
+
+    batch.draw(canvas)?;
+
+    draw_high_scores(
+        canvas,
+        table_text,
+        high_scores,
+        width * 3 / 5,
+        table_y + row_height,
+    )?;
+
+    text.set("cursor", ">")?;
+
+    for (i, item) in MENU_ITEMS.iter().enumerate() {
+        let key = format!("title_item_{}", i);
+        text.set(&key, item)?;
+
+        let x = (width - text.width(&key)) / 2;
+        let y = height * 3 / 4 + i as i32 * 12 * PIXEL_SIZE;
+
+        text.draw(canvas, &key, x, y);
+
+        if i == title.cursor {
+            text.draw(canvas, "cursor", x - 12 * PIXEL_SIZE, y);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn update_settings(
+    canvas: &mut Canvas<Window>,
+    menu: &SettingsMenu,
+    bindings: &Bindings,
+    theme: &Theme,
+    collision: CollisionMode,
+    text: &mut TextCache,
+) -> Result<(), String> {
+    canvas.set_draw_color(Color::RGB(0, 0, 0));
+    canvas.clear();
+
+    let (width, height) = canvas_size(canvas);
+
+    let label_x = width / 10;
+    let keys_x = width * 2 / 5;
+    let row_height = 10 * PIXEL_SIZE;
+    let rows_y = height / 6;
+
+    text.set("controls", "CONTROLS")?;
+    text.set("cursor", ">")?;
+    text.draw(
+        canvas,
+        "controls",
+        (width - text.width("controls")) / 2,
+        height / 20,
+    );
+
+    for (i, action) in ACTIONS.iter().enumerate() {
+        let label_key = format!("action_{}", i);
+        let keys_key = format!("action_keys_{}", i);
+        let y = rows_y + i as i32 * row_height;
+
+        text.set(&label_key, action.label())?;
+        text.set(&keys_key, &bindings.key_names(*action).to_uppercase())?;
+        text.draw(canvas, &label_key, label_x, y);
+        text.draw(canvas, &keys_key, keys_x, y);
+    }
+
+    text.set("theme", "THEME")?;
+    text.set("theme_name", theme.label)?;
+    text.draw(
+        canvas,
+        "theme",
+        label_x,
+        rows_y + THEME_ROW as i32 * row_height,
+    );
+    text.draw(
+        canvas,
+        "theme_name",
+        keys_x,
+        rows_y + THEME_ROW as i32 * row_height,
+    );
+
+    text.set("collision", "COLLISION")?;
+    text.set("collision_mode", &collision.name().to_uppercase())?;
+    text.draw(
+        canvas,
+        "collision",
+        label_x,
+        rows_y + COLLISION_ROW as i32 * row_height,
+    );
+    text.draw(
+        canvas,
+        "collision_mode",
+        keys_x,
+        rows_y + COLLISION_ROW as i32 * row_height,
+    );
+
+    text.set("reset_defaults", "RESET DEFAULTS")?;
+    text.set("settings_back", "BACK")?;
+    text.draw(
+        canvas,
+        "reset_defaults",
+        label_x,
+        rows_y + RESET_ROW as i32 * row_height,
+    );
+    text.draw(
+        canvas,
+        "settings_back",
+        label_x,
+        rows_y + BACK_ROW as i32 * row_height,
+    );
+
+    text.draw(
+        canvas,
+        "cursor",
+        label_x - 12 * PIXEL_SIZE,
+        rows_y + menu.cursor as i32 * row_height,
+    );
+
+    if !menu.message.is_empty() {
+        text.set("settings_message", &menu.message)?;
+        text.draw(
+            canvas,
+            "settings_message",
+            (width - text.width("settings_message")) / 2,
+            height * 7 / 8,
+        );
+    }
 
-    canvas.present();
+    Ok(())
 }
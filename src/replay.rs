@@ -0,0 +1,480 @@
+use std::fs;
+use std::path::Path;
+
+use crate::input::Input;
+use crate::layout::LayoutMode;
+use crate::settings::{BarrierRule, CollisionMode, Settings};
+use crate::simulation::Simulation;
+
+const MAGIC: &[u8; 4] = b"SIRP";
+pub const REPLAY_VERSION: u16 = 7;
+pub const CHECKSUM_INTERVAL: u32 = 60;
+
+// A replay only holds the inputs of ticks that were simulated while
+// playing; paused and menu ticks don't change the game and are skipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replay {
+    pub seed: u64,
+    pub settings: Settings,
+    pub checksum_interval: u32,
+    pub runs: Vec<(Input, u32)>,
+    pub checksums: Vec<u64>,
+    // The total length of the runs, kept so recording doesn't re-add them
+    // every tick.
+    ticks: u32,
+}
+
+impl Replay {
+    pub fn new(seed: u64, settings: Settings) -> Replay {
+        Replay {
+            seed,
+            settings,
+            checksum_interval: CHECKSUM_INTERVAL,
+            runs: vec![],
+            checksums: vec![],
+            ticks: 0,
+        }
+    }
+
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    // Called after each recorded step with the simulation it produced.
+    pub fn record(&mut self, input: &Input, sim: &Simulation) {
+        match self.runs.last_mut() {
+            Some((last, length)) if last == input => *length += 1,
+            _ => self.runs.push((*input, 1)),
+        }
+
+        self.ticks += 1;
+
+        if self.ticks.is_multiple_of(self.checksum_interval) {
+            self.checksums.push(sim.checksum());
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Replay, String> {
+        let bytes = fs::read(path).map_err(|e| e.to_string())?;
+        Replay::decode(&bytes)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+
+        fs::write(path, self.encode()).map_err(|e| e.to_string())
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+
+        bytes.extend_from_slice(&REPLAY_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        write_varint(&mut bytes, self.settings.lives as u64);
+        write_varint(&mut bytes, self.settings.start_wave as u64);
+        write_option(&mut bytes, self.settings.extra_life_score);
+        write_option(&mut bytes, self.settings.waves);
+        bytes.push(match self.settings.barrier_rule {
+            BarrierRule::Restore => 0,
+            BarrierRule::Keep => 1,
+        });
+        bytes.push(match self.settings.layout {
+            LayoutMode::Landscape => 0,
+            LayoutMode::Arcade => 1,
+        });
+        bytes.push(match self.settings.collision {
+            CollisionMode::Boxes => 0,
+            CollisionMode::Pixels => 1,
+        });
+        write_varint(&mut bytes, self.checksum_interval as u64);
+
+        write_varint(&mut bytes, self.runs.len() as u64);
+
+        for (input, length) in &self.runs {
+            bytes.push(encode_input(input));
+            write_varint(&mut bytes, *length as u64);
+        }
+
+        write_varint(&mut bytes, self.checksums.len() as u64);
+
+        for checksum in &self.checksums {
+            bytes.extend_from_slice(&checksum.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Replay, String> {
+        let mut reader = Reader { bytes, pos: 0 };
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(String::from("not a replay file"));
+        }
+
+        let version = u16::from_le_bytes(reader.array()?);
+
+        if version != REPLAY_VERSION {
+            return Err(format!(
+                "unsupported replay version {} (expected {})",
+                version, REPLAY_VERSION
+            ));
+        }
+
+        let seed = u64::from_le_bytes(reader.array()?);
+        let settings = Settings {
+            lives: reader.varint_u32()?,
+            start_wave: reader.varint_u32()?,
+            extra_life_score: reader.option()?,
+            waves: reader.option()?,
+            barrier_rule: match reader.byte()? {
+                0 => BarrierRule::Restore,
+                1 => BarrierRule::Keep,
+                rule => return Err(format!("invalid barrier rule {}", rule)),
+            },
+            layout: match reader.byte()? {
+                0 => LayoutMode::Landscape,
+                1 => LayoutMode::Arcade,
+                layout => return Err(format!("invalid layout {}", layout)),
+            },
+            collision: match reader.byte()? {
+                0 => CollisionMode::Boxes,
+                1 => CollisionMode::Pixels,
+                mode => return Err(format!("invalid collision mode {}", mode)),
+            },
+        };
+
+        if settings.start_wave == 0 {
+            return Err(String::from("start wave must be at least 1"));
+        }
+
+        let checksum_interval = reader.varint_u32()?;
+
+        if checksum_interval == 0 {
+            return Err(String::from("checksum interval must not be zero"));
+        }
+
+        let mut runs = vec![];
+        let mut ticks: u32 = 0;
+
+        for _ in 0..reader.varint()? {
+            let input = decode_input(reader.byte()?)?;
+            let length = reader.varint_u32()?;
+
+            if length == 0 {
+                return Err(String::from("empty input run"));
+            }
+
+            ticks = ticks
+                .checked_add(length)
+                .ok_or_else(|| String::from("replay too long"))?;
+            runs.push((input, length));
+        }
+
+        let mut checksums = vec![];
+
+        for _ in 0..reader.varint()? {
+            checksums.push(u64::from_le_bytes(reader.array()?));
+        }
+
+        if reader.pos != bytes.len() {
+            return Err(String::from("trailing data after replay"));
+        }
+
+        Ok(Replay {
+            seed,
+            settings,
+            checksum_interval,
+            runs,
+            checksums,
+            ticks,
+        })
+    }
+}
+
+pub struct Playback {
+    pub replay: Replay,
+    pub tick: u32,
+    run: usize,
+    run_offset: u32,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Playback {
+        Playback {
+            replay,
+            tick: 0,
+            run: 0,
+            run_offset: 0,
+        }
+    }
+
+    pub fn simulation(&self) -> Simulation {
+        Simulation::new(self.replay.seed, self.replay.settings)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.run >= self.replay.runs.len()
+    }
+
+    pub fn next_input(&mut self) -> Option<Input> {
+        let (input, length) = *self.replay.runs.get(self.run)?;
+
+        self.tick += 1;
+        self.run_offset += 1;
+
+        if self.run_offset >= length {
+            self.run += 1;
+            self.run_offset = 0;
+        }
+
+        Some(input)
+    }
+
+    // Called after each replayed step, mirroring `Replay::record`.
+    pub fn verify(&self, sim: &Simulation) -> Result<(), String> {
+        if !self.tick.is_multiple_of(self.replay.checksum_interval) {
+            return Ok(());
+        }
+
+        let index = (self.tick / self.replay.checksum_interval) as usize - 1;
+
+        match self.replay.checksums.get(index) {
+            Some(expected) if *expected != sim.checksum() => Err(format!(
+                "replay desynced at tick {}: checksum {:016x}, expected {:016x}",
+                self.tick,
+                sim.checksum(),
+                expected
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn encode_input(input: &Input) -> u8 {
+    input.left as u8 | (input.right as u8) << 1 | (input.fire as u8) << 2
+}
+
+fn decode_input(byte: u8) -> Result<Input, String> {
+    if byte & !0b111 != 0 {
+        return Err(format!("invalid input byte {:#04x}", byte));
+    }
+
+    Ok(Input {
+        left: byte & 1 != 0,
+        right: byte & 2 != 0,
+        fire: byte & 4 != 0,
+    })
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+
+    bytes.push(value as u8);
+}
+
+// None is stored as zero, so Some values are shifted up by one.
+fn write_option(bytes: &mut Vec<u8>, value: Option<u32>) {
+    write_varint(bytes, value.map_or(0, |value| value as u64 + 1));
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self.pos + len;
+
+        if end > self.bytes.len() {
+            return Err(String::from("unexpected end of replay"));
+        }
+
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+
+        Ok(array)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut value = 0;
+
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(String::from("varint too long"))
+    }
+
+    fn varint_u32(&mut self) -> Result<u32, String> {
+        u32::try_from(self.varint()?).map_err(|e| e.to_string())
+    }
+
+    fn option(&mut self) -> Result<Option<u32>, String> {
+        match self.varint()? {
+            0 => Ok(None),
+            value => u32::try_from(value - 1)
+                .map(Some)
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::autopilot::autopilot;
+
+    const TICKS: u32 = 600;
+
+    fn record(seed: u64, settings: Settings) -> Replay {
+        let mut sim = Simulation::new(seed, settings);
+        let mut replay = Replay::new(seed, settings);
+
+        for _ in 0..TICKS {
+            let input = autopilot(&sim);
+            sim.step(&input);
+            replay.record(&input, &sim);
+        }
+
+        replay
+    }
+
+    // Plays the replay back, stopping at the first desync.
+    fn play(replay: Replay) -> Result<u32, String> {
+        let mut playback = Playback::new(replay);
+        let mut sim = playback.simulation();
+
+        while let Some(input) = playback.next_input() {
+            sim.step(&input);
+            playback.verify(&sim)?;
+        }
+
+        Ok(playback.tick)
+    }
+
+    fn settings() -> Settings {
+        Settings {
+            start_wave: 3,
+            waves: Some(5),
+            barrier_rule: BarrierRule::Keep,
+            layout: LayoutMode::Arcade,
+            collision: CollisionMode::Pixels,
+            ..Settings::default()
+        }
+    }
+
+    #[test]
+    fn encode_decode_round_trip() {
+        let replay = record(42, settings());
+
+        assert_eq!(replay.ticks(), TICKS);
+        assert_eq!(replay.checksums.len(), (TICKS / CHECKSUM_INTERVAL) as usize);
+        assert_eq!(Replay::decode(&replay.encode()), Ok(replay));
+    }
+
+    #[test]
+    fn playback_matches_the_recording() {
+        for seed in 0..3 {
+            assert_eq!(play(record(seed, settings())), Ok(TICKS));
+        }
+    }
+
+    #[test]
+    fn playback_detects_a_desync() {
+        let mut replay = record(7, Settings::default());
+        replay.checksums[3] ^= 1;
+
+        assert_eq!(
+            play(replay).unwrap_err().split(':').next(),
+            Some("replay desynced at tick 240")
+        );
+
+        let mut replay = record(7, Settings::default());
+        replay.seed += 1;
+
+        assert!(play(replay).is_err());
+    }
+
+    #[test]
+    fn decode_rejects_truncated_input() {
+        let bytes = record(1, settings()).encode();
+
+        for len in 0..bytes.len() {
+            assert!(Replay::decode(&bytes[..len]).is_err(), "length {}", len);
+        }
+    }
+
+    #[test]
+    fn decode_rejects_corrupt_input() {
+        let bytes = record(1, Settings::default()).encode();
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert_eq!(
+            Replay::decode(&bad_magic),
+            Err(String::from("not a replay file"))
+        );
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(
+            Replay::decode(&trailing),
+            Err(String::from("trailing data after replay"))
+        );
+
+        let mut replay = Replay::new(1, Settings::default());
+        replay.runs.push((Input::default(), 0));
+        assert_eq!(
+            Replay::decode(&replay.encode()),
+            Err(String::from("empty input run"))
+        );
+
+        // The first run's input byte follows the run count.
+        let mut replay = Replay::new(1, Settings::default());
+        replay.runs.push((Input::default(), 1));
+        let mut bad_input = replay.encode();
+        let index = bad_input.len() - 3;
+        bad_input[index] = 0x08;
+        assert_eq!(
+            Replay::decode(&bad_input),
+            Err(String::from("invalid input byte 0x08"))
+        );
+    }
+
+    #[test]
+    fn decode_rejects_other_versions() {
+        let mut bytes = record(1, Settings::default()).encode();
+        bytes[MAGIC.len()..MAGIC.len() + 2].copy_from_slice(&(REPLAY_VERSION - 1).to_le_bytes());
+
+        assert_eq!(
+            Replay::decode(&bytes),
+            Err(format!(
+                "unsupported replay version {} (expected {})",
+                REPLAY_VERSION - 1,
+                REPLAY_VERSION
+            ))
+        );
+    }
+}
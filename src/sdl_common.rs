@@ -1,32 +1,116 @@
-use sdl2::{render, video, EventPump, EventSubsystem};
+use std::collections::HashSet;
 
-use crate::game::{CANVAS_HEIGHT, CANVAS_WIDTH};
+use sdl2::{render, video, AudioSubsystem, EventPump, EventSubsystem, GameControllerSubsystem};
+
+use space_invaders_rust::input::Input;
+use space_invaders_rust::layout::Layout;
+
+use crate::bindings::Action;
+use crate::cli::{Options, Scaling};
+
+// Share of the desktop a default-sized window may cover.
+const DESKTOP_FILL: f32 = 0.9;
 
 pub struct RetryEvent;
 pub struct ContinueEvent;
 
-pub fn init() -> Result<(render::Canvas<video::Window>, EventSubsystem, EventPump), String> {
+fn window_size(options: &Options, layout: &Layout, desktop: Option<(u32, u32)>) -> (u32, u32) {
+    let aspect = layout.width as f32 / layout.height as f32;
+
+    // A derived side can round down to nothing, which SDL refuses.
+    let derived = |size: f32| (size as u32).max(1);
+
+    match (options.width, options.height) {
+        (Some(width), Some(height)) => (width, height),
+        (Some(width), None) => (width, derived(width as f32 / aspect)),
+        (None, Some(height)) => (derived(height as f32 * aspect), height),
+        (None, None) => {
+            let (max_width, max_height) = match desktop {
+                Some((width, height)) => {
+                    (width as f32 * DESKTOP_FILL, height as f32 * DESKTOP_FILL)
+                }
+                None => (layout.width as f32, layout.height as f32),
+            };
+
+            let scale = (max_width / layout.width as f32)
+                .min(max_height / layout.height as f32)
+                .min(1.0);
+
+            (
+                derived(layout.width as f32 * scale),
+                derived(layout.height as f32 * scale),
+            )
+        }
+    }
+}
+
+// The canvas, the event queue and pump, the audio if a device opened, and
+// the game controllers.
+type Subsystems = (
+    render::Canvas<video::Window>,
+    EventSubsystem,
+    EventPump,
+    Result<AudioSubsystem, String>,
+    GameControllerSubsystem,
+);
+
+pub fn init(options: &Options, layout: &Layout) -> Result<Subsystems, String> {
     let sdl_context = sdl2::init()?;
     let video_subsystem = sdl_context.video()?;
 
-    let window = video_subsystem
-        .window(
-            "Space Invaders: Rust",
-            CANVAS_WIDTH as u32,
-            CANVAS_HEIGHT as u32,
-        )
-        .position_centered()
-        .opengl()
+    let desktop = video_subsystem
+        .desktop_display_mode(0)
+        .ok()
+        .map(|mode| (mode.w as u32, mode.h as u32));
+    let (width, height) = window_size(options, layout, desktop);
+
+    let mut window_builder = video_subsystem.window("Space Invaders: Rust", width, height);
+
+    window_builder.position_centered().resizable().opengl();
+
+    if options.fullscreen {
+        window_builder.fullscreen_desktop();
+    }
+
+    let window = window_builder.build().map_err(|e| e.to_string())?;
+
+    // Lets SDL merge consecutive copies from the sprite atlas into one draw
+    // call; it is only on by default when no render driver is requested.
+    sdl2::hint::set("SDL_RENDER_BATCHING", "1");
+
+    let mut canvas = window
+        .into_canvas()
+        .present_vsync()
         .build()
         .map_err(|e| e.to_string())?;
 
-    let canvas = window.into_canvas().build().map_err(|e| e.to_string())?;
+    // Everything is drawn in canvas coordinates; SDL scales that to the
+    // window and letterboxes the rest, recomputing it whenever the window
+    // is resized.
+    canvas
+        .set_logical_size(layout.width as u32, layout.height as u32)
+        .map_err(|e| e.to_string())?;
+    canvas
+        .set_integer_scale(options.scaling == Scaling::Integer)
+        .map_err(|e| e.to_string())?;
 
+    // Sound is optional, so a missing audio device is left for the caller
+    // to report rather than stopping the game.
+    let audio = sdl_context.audio();
+    let game_controller = sdl_context.game_controller()?;
     let event = sdl_context.event()?;
     let event_pump = sdl_context.event_pump()?;
 
     event.register_custom_event::<RetryEvent>()?;
     event.register_custom_event::<ContinueEvent>()?;
 
-    Ok((canvas, event, event_pump))
+    Ok((canvas, event, event_pump, audio, game_controller))
+}
+
+pub fn get_input(actions: &HashSet<Action>) -> Input {
+    Input {
+        left: actions.contains(&Action::MoveLeft),
+        right: actions.contains(&Action::MoveRight),
+        fire: actions.contains(&Action::Fire),
+    }
 }
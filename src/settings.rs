@@ -0,0 +1,57 @@
+use crate::layout::LayoutMode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarrierRule {
+    Restore,
+    Keep,
+}
+
+// How shots and sprites are tested against each other: by their bounding
+// boxes, or by the set pixels of their current frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollisionMode {
+    Boxes,
+    Pixels,
+}
+
+impl CollisionMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CollisionMode::Boxes => "boxes",
+            CollisionMode::Pixels => "pixels",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<CollisionMode> {
+        match value {
+            "boxes" => Some(CollisionMode::Boxes),
+            "pixels" => Some(CollisionMode::Pixels),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Settings {
+    pub lives: u32,
+    pub start_wave: u32,
+    pub extra_life_score: Option<u32>,
+    pub waves: Option<u32>,
+    pub barrier_rule: BarrierRule,
+    pub layout: LayoutMode,
+    pub collision: CollisionMode,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            lives: 3,
+            start_wave: 1,
+            extra_life_score: Some(1500),
+            waves: None,
+            barrier_rule: BarrierRule::Restore,
+            layout: LayoutMode::Landscape,
+            collision: CollisionMode::Boxes,
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::HashSet;
+
+use sdl2::keyboard::Keycode;
+
+use space_invaders_rust::settings::CollisionMode;
+
+use crate::bindings::{Action, Bindings, ACTIONS};
+use crate::theme::Theme;
+
+pub const THEME_ROW: usize = ACTIONS.len();
+pub const COLLISION_ROW: usize = ACTIONS.len() + 1;
+pub const RESET_ROW: usize = ACTIONS.len() + 2;
+pub const BACK_ROW: usize = ACTIONS.len() + 3;
+const ROWS: usize = ACTIONS.len() + 4;
+
+pub struct SettingsMenu {
+    pub cursor: usize,
+    pub capturing: bool,
+    pub message: String,
+    prev_actions: HashSet<Action>,
+    prev_keys: HashSet<Keycode>,
+}
+
+impl SettingsMenu {
+    pub fn new(actions: &HashSet<Action>, keys: &HashSet<Keycode>) -> SettingsMenu {
+        SettingsMenu {
+            cursor: 0,
+            capturing: false,
+            message: String::new(),
+            prev_actions: actions.clone(),
+            prev_keys: keys.clone(),
+        }
+    }
+
+    fn capture(&mut self, bindings: &mut Bindings, key: Keycode) {
+        let action = ACTIONS[self.cursor];
+
+        let result = if bindings.is_bound(action, key) {
+            bindings.unbind(action, key)
+        } else {
+            bindings.bind(action, key)
+        };
+
+        self.message = match result {
+            Ok(()) => String::new(),
+            Err(err) => err,
+        };
+        self.capturing = false;
+    }
+
+    // Returns true once the player backs out of the menu.
+    pub fn update(
+        &mut self,
+        bindings: &mut Bindings,
+        theme: &mut &'static Theme,
+        collision: &mut CollisionMode,
+        actions: &HashSet<Action>,
+        keys: &HashSet<Keycode>,
+    ) -> bool {
+        let new_actions = actions - &self.prev_actions;
+        let new_keys = keys - &self.prev_keys;
+        self.prev_actions = actions.clone();
+        self.prev_keys = keys.clone();
+
+        // Escape can't be rebound; it backs out of the capture and keeps the
+        // old binding.
+        if self.capturing {
+            if new_keys.contains(&Keycode::Escape) {
+                self.capturing = false;
+                self.message.clear();
+            } else if let Some(key) = new_keys.into_iter().next() {
+                self.capture(bindings, key);
+                self.prev_actions = bindings.actions(keys);
+            }
+
+            return false;
+        }
+
+        if new_actions.contains(&Action::MenuUp) {
+            self.cursor = (self.cursor + ROWS - 1) % ROWS;
+            self.message.clear();
+        }
+
+        if new_actions.contains(&Action::MenuDown) {
+            self.cursor = (self.cursor + 1) % ROWS;
+            self.message.clear();
+        }
+
+        if new_actions.contains(&Action::Back) {
+            return true;
+        }
+
+        if new_actions.contains(&Action::Confirm) {
+            match self.cursor {
+                THEME_ROW => *theme = theme.next(),
+                COLLISION_ROW => {
+                    *collision = match collision {
+                        CollisionMode::Boxes => CollisionMode::Pixels,
+                        CollisionMode::Pixels => CollisionMode::Boxes,
+                    };
+                    self.message = String::from("APPLIES FROM THE NEXT GAME");
+                }
+                RESET_ROW => {
+                    *bindings = Bindings::default();
+                    self.message = String::from("DEFAULTS RESTORED");
+                }
+                BACK_ROW => return true,
+                _ => {
+                    self.capturing = true;
+                    self.message = format!("PRESS A KEY FOR {}", ACTIONS[self.cursor].label());
+                }
+            }
+        }
+
+        false
+    }
+}
@@ -0,0 +1,206 @@
+use std::hash::{Hash, Hasher};
+
+use crate::collision::{self, Broadphase};
+use crate::game::{Game, GameEvent, State};
+use crate::input::Input;
+use crate::player::Player;
+use crate::settings::Settings;
+use crate::timer::{Timer, RESPAWN_TIME};
+use crate::util::Fnv1a;
+
+pub struct Simulation {
+    pub game: Game,
+    pub player: Player,
+    pub timer: Timer,
+    broadphase: Broadphase,
+}
+
+impl Simulation {
+    pub fn new(seed: u64, settings: Settings) -> Self {
+        let mut game = Game::new(seed, settings);
+        let player = Player::new(&game.layout);
+        let timer = Timer::new(&mut game);
+        let broadphase = Broadphase::new(&game.layout);
+
+        Simulation {
+            game,
+            player,
+            timer,
+            broadphase,
+        }
+    }
+
+    pub fn step(&mut self, input: &Input) {
+        self.game.events.clear();
+
+        if self.game.state == State::Playing {
+            self.game.store_prev_rects();
+            self.player.store_prev_rects();
+
+            collision::update(&mut self.broadphase, &mut self.player, &mut self.game);
+
+            if self.player.game_object.is_destroyed {
+                self.update_player_death();
+            }
+
+            self.game.update(&self.timer);
+
+            let shots_fired = self.player.shots_fired;
+            self.player.update(input);
+
+            if self.player.shots_fired != shots_fired {
+                self.game.events.push(GameEvent::PlayerShot);
+            }
+        }
+
+        self.timer.update(&mut self.game, &self.player);
+    }
+
+    pub fn checksum(&self) -> u64 {
+        let mut hasher = Fnv1a::default();
+
+        self.game.checksum().hash(&mut hasher);
+        self.player.game_object.hash(&mut hasher);
+        self.player.bullets.hash(&mut hasher);
+        self.timer.time.hash(&mut hasher);
+        self.timer.ufo_timer.hash(&mut hasher);
+
+        hasher.finish()
+    }
+
+    fn update_player_death(&mut self) {
+        if self.timer.player_explosion_timer == 0 {
+            self.game.lose_life();
+            self.game.events.push(GameEvent::PlayerKilled);
+        } else if self.timer.player_explosion_timer >= RESPAWN_TIME {
+            if self.game.lives == 0 {
+                self.game.set_game_over();
+            } else {
+                self.player.respawn();
+                self.game.clear_invader_shots();
+                self.timer.player_explosion_timer = 0;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+    use crate::game::GameObject;
+
+    fn inputs(count: usize) -> Vec<Input> {
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+
+        (0..count)
+            .map(|_| Input {
+                left: rng.gen_bool(0.3),
+                right: rng.gen_bool(0.3),
+                fire: rng.gen_bool(0.2),
+            })
+            .collect()
+    }
+
+    fn run(seed: u64, inputs: &[Input]) -> Vec<u64> {
+        let mut sim = Simulation::new(seed, Settings::default());
+
+        inputs
+            .iter()
+            .map(|input| {
+                sim.step(input);
+                sim.checksum()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_and_inputs_give_same_checksums() {
+        let inputs = inputs(3000);
+
+        for seed in 0..5 {
+            assert_eq!(run(seed, &inputs), run(seed, &inputs), "seed {}", seed);
+        }
+    }
+
+    // Pins the whole run, RNG included. If a change to the simulation is
+    // meant to alter it, bump `REPLAY_VERSION` and update the value.
+    #[test]
+    fn seeded_run_matches_the_recorded_checksum() {
+        assert_eq!(run(1, &inputs(600)).last(), Some(&0x28a6_57a6_95f6_e620));
+    }
+
+    #[test]
+    fn different_seeds_diverge() {
+        let inputs = inputs(3000);
+
+        assert_ne!(run(1, &inputs).last(), run(2, &inputs).last());
+    }
+
+    fn shot_player(sim: &mut Simulation) {
+        sim.player.game_object.is_destroyed = true;
+        sim.step(&Input::default());
+    }
+
+    #[test]
+    fn a_hit_costs_one_life() {
+        let mut sim = Simulation::new(1, Settings::default());
+        shot_player(&mut sim);
+
+        assert_eq!(sim.game.lives, 2);
+        assert!(sim.game.events.contains(&GameEvent::PlayerKilled));
+
+        // The explosion plays out without taking any more lives.
+        for _ in 0..RESPAWN_TIME {
+            sim.step(&Input::default());
+        }
+
+        assert_eq!(sim.game.lives, 2);
+        assert!(!sim.player.game_object.is_destroyed);
+    }
+
+    #[test]
+    fn respawning_clears_invader_shots() {
+        let mut sim = Simulation::new(1, Settings::default());
+        shot_player(&mut sim);
+
+        sim.game.invader_shots.push(GameObject::new(
+            100.0,
+            100.0,
+            3,
+            7,
+            String::from("invader_shot_texture"),
+        ));
+        sim.timer.player_explosion_timer = RESPAWN_TIME;
+        sim.update_player_death();
+
+        assert!(!sim.player.game_object.is_destroyed);
+        assert!(sim.game.invader_shots.is_empty());
+        assert_eq!(sim.timer.player_explosion_timer, 0);
+    }
+
+    #[test]
+    fn losing_the_last_life_ends_the_game() {
+        let mut sim = Simulation::new(
+            1,
+            Settings {
+                lives: 1,
+                ..Settings::default()
+            },
+        );
+        shot_player(&mut sim);
+
+        assert_eq!(sim.game.lives, 0);
+        assert!(sim.game.state == State::Playing);
+
+        for _ in 0..RESPAWN_TIME {
+            sim.step(&Input::default());
+        }
+
+        assert!(sim.game.state == State::GameOver);
+        assert!(sim.game.state.is_finished());
+        assert!(sim.player.game_object.is_destroyed);
+    }
+}
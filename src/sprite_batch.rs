@@ -0,0 +1,111 @@
+use sdl2::pixels::Color;
+use sdl2::rect::{FRect, Rect};
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+
+use space_invaders_rust::game::GameObject;
+
+use crate::atlas::Atlas;
+use crate::draw::to_sdl_rect;
+use crate::theme::Theme;
+
+// Back to front.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Layer {
+    Invaders,
+    InvaderShots,
+    Explosions,
+    Barriers,
+    Player,
+    Ufo,
+    Bullets,
+    Hud,
+}
+
+struct Sprite {
+    layer: Layer,
+    color: Color,
+    src: Rect,
+    dst: FRect,
+}
+
+// Collects a frame's sprites and draws them from the atlas in layer order.
+// Sprites sharing a layer and tint are drawn back to back, which SDL merges
+// into a single draw call.
+pub struct SpriteBatch<'t, 'a> {
+    atlas: &'t mut Atlas<'a>,
+    theme: &'t Theme,
+    sprites: Vec<Sprite>,
+}
+
+impl<'t, 'a> SpriteBatch<'t, 'a> {
+    pub fn new(atlas: &'t mut Atlas<'a>, theme: &'t Theme) -> SpriteBatch<'t, 'a> {
+        SpriteBatch {
+            atlas,
+            theme,
+            sprites: vec![],
+        }
+    }
+
+    pub fn push(&mut self, layer: Layer, name: &str, dst: FRect) {
+        self.sprites.push(Sprite {
+            layer,
+            color: self.theme.color(name),
+            src: self.atlas.region(name),
+            dst,
+        });
+    }
+
+    pub fn push_rect(&mut self, layer: Layer, name: &str, dst: Rect) {
+        self.push(
+            layer,
+            name,
+            FRect::new(
+                dst.x() as f32,
+                dst.y() as f32,
+                dst.width() as f32,
+                dst.height() as f32,
+            ),
+        );
+    }
+
+    pub fn push_object(&mut self, layer: Layer, object: &GameObject, alpha: f32) {
+        self.push(
+            layer,
+            &object.texture_name,
+            to_sdl_rect(&object.interpolated_rect(alpha)),
+        );
+    }
+
+    // Animated objects alternate between the `_1` and `_2` frames. They move
+    // in whole formation steps, so they are drawn where the last tick left
+    // them rather than sliding between ticks.
+    pub fn push_anim(&mut self, layer: Layer, object: &GameObject, anim_step: i32) {
+        let frame = if anim_step == 1 { 1 } else { 2 };
+
+        self.push(
+            layer,
+            &format!("{}_{}", object.texture_name, frame),
+            to_sdl_rect(&object.rect),
+        );
+    }
+
+    pub fn draw(&mut self, canvas: &mut Canvas<Window>) -> Result<(), String> {
+        self.sprites
+            .sort_by_key(|sprite| (sprite.layer, sprite.color.rgb()));
+
+        let mut tint = None;
+
+        for sprite in self.sprites.drain(..) {
+            if tint != Some(sprite.color) {
+                let Color { r, g, b, .. } = sprite.color;
+                self.atlas.texture.set_color_mod(r, g, b);
+                tint = Some(sprite.color);
+            }
+
+            canvas.copy_f(&self.atlas.texture, sprite.src, sprite.dst)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,247 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_MANIFEST_PATH: &str = "./assets/sprites.txt";
+
+// One named rectangle cut out of a sprite sheet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpriteFrame {
+    pub name: String,
+    pub sheet: PathBuf,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub line: usize,
+}
+
+impl SpriteFrame {
+    pub fn check_bounds(&self, sheet_width: u32, sheet_height: u32) -> Result<(), String> {
+        if self.x as u64 + self.width as u64 > sheet_width as u64
+            || self.y as u64 + self.height as u64 > sheet_height as u64
+        {
+            return Err(format!(
+                "frame '{}' lies outside the {}x{} sheet",
+                self.name, sheet_width, sheet_height
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+// A manifest lists sheets and the frames taken from them:
+//
+//     sheet = invaders.png
+//     invader_texture1_1 = 0 0 12 8
+//     invader_texture1_2 = 12 0 12 8
+//
+// Each frame comes from the last sheet named above it. Sheet paths are
+// relative to the manifest.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpriteManifest {
+    pub path: PathBuf,
+    pub frames: Vec<SpriteFrame>,
+}
+
+impl SpriteManifest {
+    // A missing manifest isn't an error; the built-in sprites are used.
+    pub fn load(path: &Path) -> Result<Option<SpriteManifest>, String> {
+        match fs::read_to_string(path) {
+            Ok(contents) => SpriteManifest::parse(&contents, path).map(Some),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(format!("{}: {}", path.display(), err)),
+        }
+    }
+
+    pub fn parse(contents: &str, path: &Path) -> Result<SpriteManifest, String> {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let mut manifest = SpriteManifest {
+            path: path.to_path_buf(),
+            frames: vec![],
+        };
+        let mut sheet: Option<PathBuf> = None;
+
+        for (i, line) in contents.lines().enumerate() {
+            let error =
+                |message: String| format!("{}: line {}: {}", path.display(), i + 1, message);
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .ok_or_else(|| error(String::from("expected key = value")))?;
+
+            if key.is_empty() || value.is_empty() {
+                return Err(error(String::from("expected key = value")));
+            }
+
+            if key == "sheet" {
+                sheet = Some(dir.join(value));
+                continue;
+            }
+
+            let sheet = sheet
+                .clone()
+                .ok_or_else(|| error(format!("frame '{}' comes before any sheet", key)))?;
+
+            if manifest.frames.iter().any(|frame| frame.name == key) {
+                return Err(error(format!("frame '{}' is defined twice", key)));
+            }
+
+            let numbers: Vec<&str> = value.split_whitespace().collect();
+
+            let [x, y, width, height] = numbers[..] else {
+                return Err(error(format!(
+                    "frame '{}' needs x y width height, found '{}'",
+                    key, value
+                )));
+            };
+
+            let number = |field: &str, value: &str| {
+                value.parse::<u32>().map_err(|_| {
+                    error(format!(
+                        "frame '{}' has an invalid {} '{}'",
+                        key, field, value
+                    ))
+                })
+            };
+
+            let frame = SpriteFrame {
+                name: String::from(key),
+                sheet,
+                x: number("x", x)?,
+                y: number("y", y)?,
+                width: number("width", width)?,
+                height: number("height", height)?,
+                line: i + 1,
+            };
+
+            if frame.width == 0 || frame.height == 0 {
+                return Err(error(format!("frame '{}' has no area", key)));
+            }
+
+            manifest.frames.push(frame);
+        }
+
+        Ok(manifest)
+    }
+
+    // Sheets in the order they are first used.
+    pub fn sheets(&self) -> Vec<&Path> {
+        let mut sheets: Vec<&Path> = vec![];
+
+        for frame in &self.frames {
+            if !sheets.contains(&frame.sheet.as_path()) {
+                sheets.push(&frame.sheet);
+            }
+        }
+
+        sheets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(contents: &str) -> Result<SpriteManifest, String> {
+        SpriteManifest::parse(contents, Path::new("assets/sprites.txt"))
+    }
+
+    #[test]
+    fn valid_manifest_round_trips() {
+        let manifest = parse(
+            "# invaders\n\
+             sheet = invaders.png\n\
+             invader_texture1_1 = 0 0 12 8\n\
+             \n\
+             invader_texture1_2 = 12 0 12 8\n\
+             sheet = player.png\n\
+             player_texture = 0 0 13 8\n",
+        )
+        .unwrap();
+
+        let frame = |name: &str, sheet: &str, x, width, line| SpriteFrame {
+            name: String::from(name),
+            sheet: PathBuf::from("assets").join(sheet),
+            x,
+            y: 0,
+            width,
+            height: 8,
+            line,
+        };
+
+        assert_eq!(
+            manifest.frames,
+            vec![
+                frame("invader_texture1_1", "invaders.png", 0, 12, 3),
+                frame("invader_texture1_2", "invaders.png", 12, 12, 5),
+                frame("player_texture", "player.png", 0, 13, 7),
+            ]
+        );
+        assert_eq!(
+            manifest.sheets(),
+            vec![
+                Path::new("assets/invaders.png"),
+                Path::new("assets/player.png")
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_field_is_an_error() {
+        let err = parse("sheet = a.png\nufo_texture = 0 0 16\n").unwrap_err();
+
+        assert_eq!(
+            err,
+            "assets/sprites.txt: line 2: frame 'ufo_texture' needs x y width height, found '0 0 16'"
+        );
+    }
+
+    #[test]
+    fn bad_number_is_an_error() {
+        let err = parse("sheet = a.png\nufo_texture = 0 -1 16 7\n").unwrap_err();
+
+        assert_eq!(
+            err,
+            "assets/sprites.txt: line 2: frame 'ufo_texture' has an invalid y '-1'"
+        );
+    }
+
+    #[test]
+    fn duplicate_name_is_an_error() {
+        let err =
+            parse("sheet = a.png\nufo_texture = 0 0 16 7\nufo_texture = 16 0 16 7\n").unwrap_err();
+
+        assert_eq!(
+            err,
+            "assets/sprites.txt: line 3: frame 'ufo_texture' is defined twice"
+        );
+    }
+
+    #[test]
+    fn out_of_bounds_rect_is_an_error() {
+        let manifest = parse("sheet = a.png\nufo_texture = 8 0 16 7\n").unwrap();
+        let frame = &manifest.frames[0];
+
+        assert_eq!(frame.check_bounds(24, 7), Ok(()));
+        assert_eq!(
+            frame.check_bounds(23, 7),
+            Err(String::from(
+                "frame 'ufo_texture' lies outside the 23x7 sheet"
+            ))
+        );
+        assert!(frame.check_bounds(24, 6).is_err());
+
+        let huge = parse("sheet = a.png\nufo_texture = 4294967295 0 1 7\n").unwrap();
+
+        assert!(huge.frames[0].check_bounds(u32::MAX, 7).is_err());
+        assert!(parse("sheet = a.png\nufo_texture = 0 0 0 7\n").is_err());
+    }
+}
@@ -0,0 +1,164 @@
+use std::f32::consts::TAU;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Waveform {
+    Square,
+    Noise,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Envelope {
+    pub attack: f32,
+    pub decay: f32,
+    pub sustain: f32,
+    pub release: f32,
+}
+
+impl Envelope {
+    fn level(&self, t: f32, duration: f32) -> f32 {
+        let release_start = (duration - self.release).max(0.0);
+
+        let level = if t < self.attack {
+            t / self.attack
+        } else if t < self.attack + self.decay {
+            1.0 - (1.0 - self.sustain) * (t - self.attack) / self.decay
+        } else {
+            self.sustain
+        };
+
+        if t >= release_start && self.release > 0.0 {
+            level * (1.0 - (t - release_start) / self.release).max(0.0)
+        } else {
+            level
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Voice {
+    pub waveform: Waveform,
+    pub start_freq: f32,
+    pub end_freq: f32,
+    pub duration: f32,
+    pub envelope: Envelope,
+    pub volume: f32,
+    // Frequency modulation as (rate in Hz, depth as a fraction of the pitch).
+    pub vibrato: Option<(f32, f32)>,
+}
+
+struct Oscillator {
+    phase: f32,
+    lfsr: u16,
+    noise: f32,
+}
+
+impl Oscillator {
+    fn new() -> Oscillator {
+        Oscillator {
+            phase: 0.0,
+            lfsr: 0x7fff,
+            noise: 1.0,
+        }
+    }
+
+    fn next(&mut self, waveform: Waveform, freq: f32, sample_rate: f32) -> f32 {
+        self.phase += freq / sample_rate;
+
+        while self.phase >= 1.0 {
+            self.phase -= 1.0;
+
+            // Sample-and-hold noise clocked at the voice pitch, like the
+            // 15-bit shift register in the arcade's sound board.
+            let bit = (self.lfsr ^ (self.lfsr >> 1)) & 1;
+            self.lfsr = (self.lfsr >> 1) | (bit << 14);
+            self.noise = if self.lfsr & 1 == 0 { 1.0 } else { -1.0 };
+        }
+
+        match waveform {
+            Waveform::Square => {
+                if self.phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            Waveform::Noise => self.noise,
+        }
+    }
+}
+
+pub fn render(voices: &[Voice], sample_rate: u32) -> Vec<f32> {
+    let rate = sample_rate as f32;
+
+    let length = voices
+        .iter()
+        .map(|voice| (voice.duration * rate) as usize)
+        .max()
+        .unwrap_or(0);
+
+    let mut samples = vec![0.0; length];
+
+    for voice in voices {
+        let mut oscillator = Oscillator::new();
+        let voice_length = (voice.duration * rate) as usize;
+
+        for (i, sample) in samples.iter_mut().take(voice_length).enumerate() {
+            let t = i as f32 / rate;
+            let progress = t / voice.duration;
+
+            let mut freq = voice.start_freq * (voice.end_freq / voice.start_freq).powf(progress);
+
+            if let Some((vibrato_rate, depth)) = voice.vibrato {
+                freq *= 1.0 + depth * (TAU * vibrato_rate * t).sin();
+            }
+
+            *sample += oscillator.next(voice.waveform, freq, rate)
+                * voice.envelope.level(t, voice.duration)
+                * voice.volume;
+        }
+    }
+
+    samples
+}
+
+// Encodes the samples in whichever SDL audio format the device opened with.
+// The format's low byte is the sample size in bits; the flags above it mark
+// float, big-endian and signed samples.
+pub fn to_pcm(samples: &[f32], channels: usize, format: u16) -> Result<Box<[u8]>, String> {
+    let bits = (format & 0xff) as u32;
+    let float = format & 0x100 != 0;
+    let big_endian = format & 0x1000 != 0;
+    let signed = format & 0x8000 != 0;
+
+    if !matches!((bits, float), (8 | 16 | 32, false) | (32, true)) {
+        return Err(format!("unsupported audio format: {:#x}", format));
+    }
+
+    let size = bits as usize / 8;
+    let max = ((1_i64 << (bits - 1)) - 1) as f64;
+    let mut pcm = Vec::with_capacity(samples.len() * channels * size);
+
+    for sample in samples {
+        let sample = sample.clamp(-1.0, 1.0);
+
+        let value = if float {
+            sample.to_bits()
+        } else if signed {
+            (sample as f64 * max) as i64 as u32
+        } else {
+            (sample as f64 * max + max + 1.0) as u32
+        };
+
+        let mut bytes = value.to_le_bytes();
+
+        if big_endian {
+            bytes[..size].reverse();
+        }
+
+        for _ in 0..channels {
+            pcm.extend_from_slice(&bytes[..size]);
+        }
+    }
+
+    Ok(pcm.into_boxed_slice())
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, Texture, TextureCreator};
+use sdl2::ttf::{Font, Sdl2TtfContext};
+use sdl2::video::{Window, WindowContext};
+
+use crate::draw::draw_texture_nameless_rect;
+use crate::ui::FONT_PATH;
+
+pub struct TextCache<'ttf, 'a> {
+    font: Font<'ttf, 'static>,
+    texture_creator: &'a TextureCreator<WindowContext>,
+    texts: HashMap<String, (String, Texture<'a>)>,
+}
+
+impl<'ttf, 'a> TextCache<'ttf, 'a> {
+    pub fn new(
+        ttf_context: &'ttf Sdl2TtfContext,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        size: u16,
+    ) -> Result<TextCache<'ttf, 'a>, String> {
+        Ok(TextCache {
+            font: ttf_context.load_font(FONT_PATH, size)?,
+            texture_creator,
+            texts: HashMap::new(),
+        })
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        if let Some((cached, _)) = self.texts.get(key) {
+            if cached == value {
+                return Ok(());
+            }
+        }
+
+        let surface = self
+            .font
+            .render(value)
+            .blended(Color::RGB(255, 255, 255))
+            .map_err(|e| e.to_string())?;
+
+        let texture = self
+            .texture_creator
+            .create_texture_from_surface(&surface)
+            .map_err(|e| e.to_string())?;
+
+        self.texts
+            .insert(String::from(key), (String::from(value), texture));
+
+        Ok(())
+    }
+
+    pub fn width(&self, key: &str) -> i32 {
+        match self.texts.get(key) {
+            Some((_, texture)) => texture.query().width as i32,
+            None => 0,
+        }
+    }
+
+    pub fn draw(&self, canvas: &mut Canvas<Window>, key: &str, x: i32, y: i32) -> i32 {
+        match self.texts.get(key) {
+            Some((_, texture)) => {
+                let query = texture.query();
+
+                draw_texture_nameless_rect(
+                    canvas,
+                    texture,
+                    &Rect::new(x, y, query.width, query.height),
+                );
+
+                x + query.width as i32
+            }
+            None => x,
+        }
+    }
+}
@@ -87,6 +87,17 @@ pub const INVADER_SHOT: [[u32; 3]; 7] = [
     [0, 0, 1],
 ];
 
+pub const INVADER_SHOT_EXPLOSION: [[u32; 6]; 8] = [
+    [0, 1, 0, 1, 0, 1],
+    [1, 0, 1, 0, 1, 0],
+    [0, 1, 1, 1, 1, 1],
+    [1, 0, 1, 1, 1, 0],
+    [0, 1, 1, 1, 1, 0],
+    [0, 0, 1, 1, 0, 1],
+    [1, 0, 0, 0, 1, 0],
+    [0, 0, 1, 0, 0, 0],
+];
+
 pub const BARRIER: [[u32; 24]; 18] = [
     [
         0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0,
@@ -1,16 +1,18 @@
 use std::collections::HashMap;
 
+use sdl2::image::LoadSurface;
 use sdl2::pixels::Color;
 use sdl2::pixels::PixelFormatEnum;
 use sdl2::rect::Rect;
-use sdl2::render::{Canvas, Texture, TextureCreator};
+use sdl2::render::{BlendMode, Canvas, Texture, TextureCreator};
+use sdl2::surface::Surface;
 use sdl2::video::{Window, WindowContext};
 
-use crate::barrier::Collider;
-use crate::game::PIXEL_SIZE;
-use crate::texture_templates::{
-    BARRIER, BARRIER_MASK, EXPLOSION, INVADER_1_1, INVADER_1_2, INVADER_2_1, INVADER_2_2,
-    INVADER_3_1, INVADER_3_2, INVADER_SHOT, MISSING_TEXTURE, PLAYER, SHOT, UFO,
+use space_invaders_rust::game::PIXEL_SIZE;
+use space_invaders_rust::sprite_sheet::{SpriteFrame, SpriteManifest};
+use space_invaders_rust::texture_templates::{
+    BARRIER, EXPLOSION, INVADER_1_1, INVADER_1_2, INVADER_2_1, INVADER_2_2, INVADER_3_1,
+    INVADER_3_2, INVADER_SHOT, MISSING_TEXTURE, PLAYER, SHOT, UFO,
 };
 
 fn get_texture_from_matrix<'a, const T: usize, const M: usize>(
@@ -111,7 +113,7 @@ pub fn textures<'a>(
         canvas,
         texture_creator,
         PLAYER,
-        Color::RGB(50, 255, 0),
+        Color::RGB(255, 255, 255),
         None,
     )?;
 
@@ -135,23 +137,18 @@ pub fn textures<'a>(
         canvas,
         texture_creator,
         BARRIER,
-        Color::RGB(50, 255, 0),
+        Color::RGB(255, 255, 255),
         None,
     )?;
 
-    let mut barrier_mask_texture = get_texture_from_matrix(
+    let ufo_texture = get_texture_from_matrix(
         canvas,
         texture_creator,
-        BARRIER_MASK,
-        Color::RGB(0, 0, 0),
-        Some(PixelFormatEnum::ABGR8888),
+        UFO,
+        Color::RGB(255, 255, 255),
+        None,
     )?;
 
-    barrier_mask_texture.set_blend_mode(sdl2::render::BlendMode::Blend);
-
-    let ufo_texture =
-        get_texture_from_matrix(canvas, texture_creator, UFO, Color::RGB(255, 0, 0), None)?;
-
     let mut shot_texture = texture_creator
         .create_texture_target(None, 1, SHOT.len() as u32)
         .map_err(|e| e.to_string())?;
@@ -206,56 +203,74 @@ pub fn textures<'a>(
     hash.insert(String::from("explosion_texture"), explosion_texture);
     hash.insert(String::from("invader_shot_texture"), invader_shot_texture);
     hash.insert(String::from("barrier_texture"), barrier_texture);
-    hash.insert(String::from("barrier_mask_texture"), barrier_mask_texture);
     hash.insert(String::from("ufo_texture"), ufo_texture);
 
     Ok((hash, missing_texture, empty_texture))
 }
 
-#[allow(dead_code)]
-pub fn get_collider_textures<'a>(
-    canvas: &mut Canvas<Window>,
+fn get_texture_from_sheet<'a>(
     texture_creator: &'a TextureCreator<WindowContext>,
-    colliders: &Vec<Collider>,
-) -> Result<Vec<Texture<'a>>, String> {
-    let mut targets = vec![];
-
-    for collider in colliders {
-        let target = texture_creator
-            .create_texture_target(
-                None,
-                collider.rect.width() as u32,
-                collider.rect.height() as u32,
-            )
-            .map_err(|e| e.to_string())?;
-
-        targets.push((target, collider));
-    }
+    sheet: &Surface,
+    frame: &SpriteFrame,
+) -> Result<Texture<'a>, String> {
+    frame.check_bounds(sheet.width(), sheet.height())?;
 
-    let mut targets_for_canvas = vec![];
-    for target in &mut targets {
-        targets_for_canvas.push((&mut target.0, target.1));
-    }
+    let mut surface = Surface::new(frame.width, frame.height, PixelFormatEnum::RGBA32)?;
 
-    canvas
-        .with_multiple_texture_canvas(targets_for_canvas.iter(), |texture_canvas, collider| {
-            texture_canvas.set_draw_color(Color::RGB(0, 0, 0));
-            texture_canvas.clear();
-
-            for item in 0..=collider.rect.y() as i32 {
-                texture_canvas.set_draw_color(Color::RGB(255, 0, 0));
+    sheet.blit(
+        Rect::new(frame.x as i32, frame.y as i32, frame.width, frame.height),
+        &mut surface,
+        None,
+    )?;
 
-                texture_canvas
-                    .fill_rect(Rect::new(0, item, PIXEL_SIZE as u32, PIXEL_SIZE as u32))
-                    .expect("could not draw rect");
-            }
-        })
+    let mut texture = texture_creator
+        .create_texture_from_surface(&surface)
         .map_err(|e| e.to_string())?;
 
-    let mut textures = vec![];
-    for target in targets {
-        textures.push(target.0);
-    }
+    texture.set_blend_mode(BlendMode::Blend);
 
-    Ok(textures)
+    Ok(texture)
+}
+
+// Replaces built-in sprites with frames cut from the manifest's sheets.
+// Frames that can't be loaded are reported and keep the built-in sprite.
+pub fn load_sprite_sheets<'a>(
+    texture_creator: &'a TextureCreator<WindowContext>,
+    manifest: &SpriteManifest,
+    textures: &mut HashMap<String, Texture<'a>>,
+) {
+    for sheet_path in manifest.sheets() {
+        let mut sheet = match Surface::from_file(sheet_path) {
+            Ok(sheet) => sheet,
+            Err(err) => {
+                println!("{}: {}", sheet_path.display(), err);
+                continue;
+            }
+        };
+
+        // Copy the sheet's alpha into each frame instead of blending it.
+        if let Err(err) = sheet.set_blend_mode(BlendMode::None) {
+            println!("{}: {}", sheet_path.display(), err);
+            continue;
+        }
+
+        for frame in manifest
+            .frames
+            .iter()
+            .filter(|frame| frame.sheet == sheet_path)
+        {
+            let result = if textures.contains_key(&frame.name) {
+                get_texture_from_sheet(texture_creator, &sheet, frame)
+            } else {
+                Err(format!("unknown sprite '{}'", frame.name))
+            };
+
+            match result {
+                Ok(texture) => {
+                    textures.insert(frame.name.clone(), texture);
+                }
+                Err(err) => println!("{}: line {}: {}", manifest.path.display(), frame.line, err),
+            }
+        }
+    }
 }
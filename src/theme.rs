@@ -0,0 +1,96 @@
+use sdl2::pixels::Color;
+
+use space_invaders_rust::config::Config;
+
+const WHITE: Color = Color::RGB(255, 255, 255);
+
+// Sprites are rasterized in white and tinted when drawn, either per entity
+// through the palette or per screen band through the layout's overlay.
+pub struct Theme {
+    pub name: &'static str,
+    pub label: &'static str,
+    pub overlay: bool,
+    // Texture name prefixes and their colour; anything unlisted stays white.
+    pub palette: &'static [(&'static str, Color)],
+    pub ground: Color,
+}
+
+pub const THEMES: [Theme; 4] = [
+    Theme {
+        name: "cellophane",
+        label: "CELLOPHANE",
+        overlay: true,
+        palette: &[],
+        ground: WHITE,
+    },
+    Theme {
+        name: "classic",
+        label: "CLASSIC B&W",
+        overlay: false,
+        palette: &[],
+        ground: WHITE,
+    },
+    Theme {
+        name: "high_contrast",
+        label: "HIGH CONTRAST",
+        overlay: false,
+        palette: &[
+            ("player", Color::RGB(255, 255, 0)),
+            ("shot", Color::RGB(255, 255, 0)),
+            ("barrier", Color::RGB(0, 255, 255)),
+            ("ufo", Color::RGB(255, 0, 255)),
+            ("invader_shot", Color::RGB(255, 96, 0)),
+        ],
+        ground: Color::RGB(0, 255, 255),
+    },
+    // Okabe-Ito colours, which stay distinct under the common forms of
+    // colour blindness.
+    Theme {
+        name: "colorblind",
+        label: "COLORBLIND SAFE",
+        overlay: false,
+        palette: &[
+            ("invader_texture", Color::RGB(86, 180, 233)),
+            ("player", Color::RGB(230, 159, 0)),
+            ("barrier", Color::RGB(0, 158, 115)),
+            ("ufo", Color::RGB(213, 94, 0)),
+            ("invader_shot", Color::RGB(240, 228, 66)),
+        ],
+        ground: Color::RGB(0, 158, 115),
+    },
+];
+
+impl Theme {
+    pub fn load(config: &Config) -> &'static Theme {
+        match config.get("theme") {
+            Some(name) => match THEMES.iter().find(|theme| theme.name == name) {
+                Some(theme) => theme,
+                None => {
+                    println!("theme: unknown theme '{}'", name);
+                    &THEMES[0]
+                }
+            },
+            None => &THEMES[0],
+        }
+    }
+
+    pub fn store(&self, config: &mut Config) {
+        config.set("theme", self.name);
+    }
+
+    pub fn next(&self) -> &'static Theme {
+        let index = THEMES
+            .iter()
+            .position(|theme| theme.name == self.name)
+            .unwrap_or(0);
+
+        &THEMES[(index + 1) % THEMES.len()]
+    }
+
+    pub fn color(&self, texture_name: &str) -> Color {
+        self.palette
+            .iter()
+            .find(|(prefix, _)| texture_name.starts_with(prefix))
+            .map_or(WHITE, |(_, color)| *color)
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::HashSet;
+
+use sdl2::keyboard::Keycode;
+
+use space_invaders_rust::autopilot::autopilot;
+use space_invaders_rust::game::FPS;
+use space_invaders_rust::settings::Settings;
+use space_invaders_rust::simulation::Simulation;
+
+use crate::bindings::Action;
+
+const ATTRACT_DELAY: u32 = 10 * FPS;
+const DEMO_LENGTH: u32 = 60 * FPS;
+
+pub const MENU_ITEMS: [&str; 2] = ["PRESS START", "SETTINGS"];
+
+pub enum TitleAction {
+    None,
+    Start,
+    Settings,
+    Quit,
+}
+
+pub struct Title {
+    pub demo: Option<Simulation>,
+    pub cursor: usize,
+    settings: Settings,
+    idle_ticks: u32,
+    prev_actions: HashSet<Action>,
+    prev_keys: HashSet<Keycode>,
+}
+
+impl Title {
+    pub fn new(settings: Settings) -> Title {
+        Title {
+            demo: None,
+            cursor: 0,
+            settings,
+            idle_ticks: 0,
+            prev_actions: HashSet::new(),
+            prev_keys: HashSet::new(),
+        }
+    }
+
+    pub fn reset(&mut self, actions: &HashSet<Action>, keys: &HashSet<Keycode>) {
+        self.demo = None;
+        self.idle_ticks = 0;
+        self.prev_actions = actions.clone();
+        self.prev_keys = keys.clone();
+    }
+
+    pub fn step(&mut self) {
+        self.idle_ticks += 1;
+
+        match &mut self.demo {
+            Some(demo) => {
+                demo.step(&autopilot(demo));
+
+                if demo.game.state.is_finished() || self.idle_ticks >= ATTRACT_DELAY + DEMO_LENGTH {
+                    self.demo = None;
+                    self.idle_ticks = 0;
+                }
+            }
+            None => {
+                if self.idle_ticks >= ATTRACT_DELAY {
+                    self.demo = Some(Simulation::new(rand::random(), self.settings));
+                }
+            }
+        }
+    }
+
+    // Any key interrupts the demo, bound or not, so the raw keys are
+    // tracked alongside the actions.
+    pub fn update(&mut self, actions: &HashSet<Action>, keys: &HashSet<Keycode>) -> TitleAction {
+        let new_actions = actions - &self.prev_actions;
+        let any_key = !(keys - &self.prev_keys).is_empty();
+        self.prev_actions = actions.clone();
+        self.prev_keys = keys.clone();
+
+        if !any_key && new_actions.is_empty() {
+            return TitleAction::None;
+        }
+
+        if self.demo.is_some() {
+            self.reset(actions, keys);
+            return TitleAction::None;
+        }
+
+        self.idle_ticks = 0;
+
+        if new_actions.contains(&Action::MenuUp) || new_actions.contains(&Action::MenuDown) {
+            self.cursor = (self.cursor + 1) % MENU_ITEMS.len();
+        }
+
+        if new_actions.contains(&Action::Confirm) {
+            if self.cursor == 0 {
+                TitleAction::Start
+            } else {
+                TitleAction::Settings
+            }
+        } else if new_actions.contains(&Action::Back) {
+            TitleAction::Quit
+        } else {
+            TitleAction::None
+        }
+    }
+}
@@ -1,32 +1,46 @@
-use crate::game::{GameObject, CANVAS_LEFT_EDGE, CANVAS_RIGHT_EDGE, PIXEL_SIZE};
+use crate::game::{GameObject, PIXEL_SIZE};
+use crate::layout::Layout;
+
+const MYSTERY_POINTS: [u32; 15] = [
+    100, 50, 50, 100, 150, 100, 100, 50, 300, 100, 100, 100, 50, 150, 100,
+];
 
 pub struct UFO {
     pub game_object: GameObject,
     pub dir: String,
+    pub points: u32,
+}
+
+// Like the arcade, the bonus is picked from a fixed table by the number of
+// shots fired so far. The table repeats every 15 shots, so the 8th shot is
+// worth 300, and so are the 23rd and every 15th after that.
+pub fn mystery_points(shots_fired: u32) -> u32 {
+    MYSTERY_POINTS[(shots_fired % MYSTERY_POINTS.len() as u32) as usize]
 }
 
 impl UFO {
-    pub fn new(spawn_times: u32) -> UFO {
+    pub fn new(spawn_times: u32, layout: &Layout) -> UFO {
         let width = 16 * PIXEL_SIZE as u32;
         let height = 7 * PIXEL_SIZE as u32;
 
-        let mut x = CANVAS_LEFT_EDGE as f32;
+        let mut x = layout.left_edge as f32;
         let mut dir = String::from("right");
 
-        if spawn_times % 2 != 0 {
-            x = CANVAS_RIGHT_EDGE as f32;
+        if !spawn_times.is_multiple_of(2) {
+            x = layout.right_edge as f32;
             dir = String::from("left")
         }
 
         UFO {
             game_object: GameObject::new(
                 x,
-                height as f32,
+                layout.ufo_y as f32,
                 width,
                 height,
                 String::from("ufo_texture"),
             ),
             dir,
+            points: 0,
         }
     }
 
@@ -38,3 +52,17 @@ impl UFO {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mystery_points_pay_300_every_15th_shot_from_the_8th() {
+        let big_shots: Vec<u32> = (0..60)
+            .filter(|shots_fired| mystery_points(*shots_fired) == 300)
+            .collect();
+
+        assert_eq!(big_shots, vec![8, 23, 38, 53]);
+    }
+}
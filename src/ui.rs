@@ -1,22 +1,26 @@
 use std::collections::{HashMap, HashSet};
 
 use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
 use sdl2::render::{Canvas, Texture, TextureCreator};
+use sdl2::ttf::Sdl2TtfContext;
 use sdl2::video::{Window, WindowContext};
 use sdl2::EventSubsystem;
 
-use crate::game::{State, CANVAS_HEIGHT, CANVAS_WIDTH, PIXEL_SIZE};
+use space_invaders_rust::game::{State, PIXEL_SIZE};
+use space_invaders_rust::texture_templates::ARROW;
+
+use crate::bindings::Action;
+use crate::draw::canvas_size;
 use crate::sdl_common::{ContinueEvent, RetryEvent};
-use crate::texture_templates::ARROW;
-use crate::util::{center_x, center_y};
+
+pub const FONT_PATH: &str = "./src/PressStart2P-vaV7.ttf";
 
 pub struct UI {
     pub targets: [Rect; 2],
     pub cursor_pos: usize,
-    prev_keys: HashSet<Keycode>,
+    prev_actions: HashSet<Action>,
 }
 
 impl UI {
@@ -24,10 +28,14 @@ impl UI {
         UI {
             targets: [retry_target, quit_target],
             cursor_pos: 0,
-            prev_keys: HashSet::new(),
+            prev_actions: HashSet::new(),
         }
     }
 
+    pub fn skip_actions(&mut self, actions: &HashSet<Action>) {
+        self.prev_actions = actions.clone();
+    }
+
     pub fn update_cursor(&mut self) {
         if self.cursor_pos == 0 {
             self.cursor_pos = 1;
@@ -49,7 +57,10 @@ impl UI {
 
     pub fn select(&self, event: &EventSubsystem, game_state: &State) {
         if self.cursor_pos == 0 {
-            if game_state == &State::GameOver || game_state == &State::Win {
+            if game_state == &State::GameOver
+                || game_state == &State::Invaded
+                || game_state == &State::Win
+            {
                 let result = event.push_custom_event(RetryEvent);
                 match result {
                     Ok(_) => (),
@@ -71,26 +82,29 @@ impl UI {
         }
     }
 
-    pub fn update(&mut self, keys: &HashSet<Keycode>, event: &EventSubsystem, game_state: &State) {
-        let new_keys = keys - &self.prev_keys;
-        let old_keys = &self.prev_keys - keys;
+    pub fn update(
+        &mut self,
+        actions: &HashSet<Action>,
+        event: &EventSubsystem,
+        game_state: &State,
+    ) {
+        let new_actions = actions - &self.prev_actions;
+        let old_actions = &self.prev_actions - actions;
 
-        if !new_keys.is_empty() || !old_keys.is_empty() {
-            if game_state != &State::Playing {
-                if new_keys.contains(&Keycode::Up) || new_keys.contains(&Keycode::Down) {
-                    self.update_cursor();
-                }
+        if (!new_actions.is_empty() || !old_actions.is_empty()) && game_state != &State::Playing {
+            if new_actions.contains(&Action::MenuUp) || new_actions.contains(&Action::MenuDown) {
+                self.update_cursor();
+            }
 
-                if new_keys.contains(&Keycode::Return) || new_keys.contains(&Keycode::Space) {
-                    self.select(&event, &game_state);
-                }
+            if new_actions.contains(&Action::Confirm) {
+                self.select(event, game_state);
             }
         }
 
-        self.prev_keys = keys.clone();
+        self.prev_actions = actions.clone();
     }
 
-    pub fn get_ui_targets_base_on_state<'a>(
+    pub fn get_ui_targets_base_on_state(
         &self,
         ui_targets_hash: &HashMap<String, Rect>,
         game_state: &State,
@@ -99,7 +113,7 @@ impl UI {
 
         if game_state == &State::GameOver {
             keys = [
-                String::from("game over"),
+                String::from("game_over"),
                 String::from("retry"),
                 String::from("quit"),
             ];
@@ -109,6 +123,12 @@ impl UI {
                 String::from("continue"),
                 String::from("quit"),
             ];
+        } else if game_state == &State::Invaded {
+            keys = [
+                String::from("invaded"),
+                String::from("retry"),
+                String::from("quit"),
+            ];
         } else if game_state == &State::Win {
             keys = [
                 String::from("victory"),
@@ -120,31 +140,33 @@ impl UI {
         let mut ui_targets = ui_targets_hash.clone();
         ui_targets.retain(|k, _| keys.contains(k));
 
-        return ui_targets;
+        ui_targets
     }
 }
 
+// The modal backgrounds, the menu arrow, the label textures and their
+// targets, and the modal's own rect.
+type UiTextures<'a> = (
+    HashMap<Rect, Texture<'a>>,
+    Texture<'a>,
+    HashMap<String, Texture<'a>>,
+    HashMap<String, Rect>,
+    Rect,
+);
+
 pub fn create_ui<'a>(
     canvas: &mut Canvas<Window>,
     texture_creator: &'a TextureCreator<WindowContext>,
-) -> Result<
-    (
-        HashMap<Rect, Texture<'a>>,
-        Texture<'a>,
-        HashMap<String, Texture<'a>>,
-        HashMap<String, Rect>,
-        Rect,
-    ),
-    String,
-> {
-    let ttf_context = sdl2::ttf::init().map_err(|e| e.to_string())?;
-    let font = ttf_context.load_font("./src/PressStart2P-vaV7.ttf", (16 * PIXEL_SIZE) as u16)?;
+    ttf_context: &Sdl2TtfContext,
+) -> Result<UiTextures<'a>, String> {
+    let font = ttf_context.load_font(FONT_PATH, (16 * PIXEL_SIZE) as u16)?;
+    let (width, height) = canvas_size(canvas);
 
     let modal_target = Rect::new(
-        center_x(CANVAS_WIDTH / 2),
-        center_y(CANVAS_HEIGHT / 2),
-        (CANVAS_WIDTH / 2) as u32,
-        (CANVAS_HEIGHT / 2) as u32,
+        width / 4,
+        height / 4,
+        (width / 2) as u32,
+        (height / 2) as u32,
     );
 
     let game_over_surface = font
@@ -152,6 +174,11 @@ pub fn create_ui<'a>(
         .blended(Color::RGB(255, 255, 255))
         .map_err(|e| e.to_string())?;
 
+    let invaded_surface = font
+        .render("INVADED")
+        .blended(Color::RGB(255, 255, 255))
+        .map_err(|e| e.to_string())?;
+
     let victory_surface = font
         .render("Victory!")
         .blended(Color::RGB(255, 255, 255))
@@ -176,6 +203,10 @@ pub fn create_ui<'a>(
         .create_texture_from_surface(&game_over_surface)
         .map_err(|e| e.to_string())?;
 
+    let invaded_texture = texture_creator
+        .create_texture_from_surface(&invaded_surface)
+        .map_err(|e| e.to_string())?;
+
     let victory_texture = texture_creator
         .create_texture_from_surface(&victory_surface)
         .map_err(|e| e.to_string())?;
@@ -193,6 +224,7 @@ pub fn create_ui<'a>(
         .map_err(|e| e.to_string())?;
 
     let game_over_query = game_over_texture.query();
+    let invaded_query = invaded_texture.query();
     let victory_query = victory_texture.query();
     let continue_query = continue_texture.query();
     let retry_query = retry_texture.query();
@@ -205,6 +237,13 @@ pub fn create_ui<'a>(
         game_over_query.height,
     );
 
+    let invaded_target = Rect::new(
+        modal_target.center().x() - (invaded_query.width / 2) as i32,
+        modal_target.top(),
+        invaded_query.width,
+        invaded_query.height,
+    );
+
     let victory_target = Rect::new(
         modal_target.center().x() - (victory_query.width / 2) as i32,
         modal_target.top(),
@@ -236,7 +275,7 @@ pub fn create_ui<'a>(
     let default_target = Rect::new(0, 0, 0, 0);
 
     let mut modal_texture = texture_creator
-        .create_texture_target(None, (CANVAS_WIDTH / 2) as u32, (CANVAS_HEIGHT / 2) as u32)
+        .create_texture_target(None, (width / 2) as u32, (height / 2) as u32)
         .map_err(|e| e.to_string())?;
 
     let mut arrow_texture = texture_creator
@@ -251,12 +290,7 @@ pub fn create_ui<'a>(
             texture_canvas.set_draw_color(Color::RGB(0, 0, 0));
 
             texture_canvas
-                .fill_rect(Rect::new(
-                    0,
-                    0,
-                    (CANVAS_WIDTH / 2) as u32,
-                    (CANVAS_HEIGHT / 2) as u32,
-                ))
+                .fill_rect(Rect::new(0, 0, (width / 2) as u32, (height / 2) as u32))
                 .expect("could not draw rect");
         })
         .map_err(|e| e.to_string())?;
@@ -292,6 +326,7 @@ pub fn create_ui<'a>(
 
     let mut ui_texture_hash: HashMap<String, Texture> = HashMap::new();
     ui_texture_hash.insert(String::from("game_over"), game_over_texture);
+    ui_texture_hash.insert(String::from("invaded"), invaded_texture);
     ui_texture_hash.insert(String::from("victory"), victory_texture);
     ui_texture_hash.insert(String::from("continue"), continue_texture);
     ui_texture_hash.insert(String::from("retry"), retry_texture);
@@ -299,6 +334,7 @@ pub fn create_ui<'a>(
 
     let mut ui_target_hash: HashMap<String, Rect> = HashMap::new();
     ui_target_hash.insert(String::from("game_over"), game_over_target);
+    ui_target_hash.insert(String::from("invaded"), invaded_target);
     ui_target_hash.insert(String::from("victory"), victory_target);
     ui_target_hash.insert(String::from("continue"), continue_target);
     ui_target_hash.insert(String::from("retry"), retry_target);
@@ -1,108 +1,124 @@
-use crate::game::GameObject;
-use sdl2::rect::{FRect, Rect};
-use sdl2::render::{Canvas, Texture};
-use sdl2::video::Window;
-use std::collections::HashMap;
-
-use crate::game::{CANVAS_HEIGHT, CANVAS_WIDTH};
-
-pub fn draw_texture<'a>(
-    canvas: &mut Canvas<Window>,
-    textures: &HashMap<String, Texture<'a>>,
-    missing_texture: &Texture<'a>,
-    object: &GameObject,
-) {
-    let result = canvas.copy_f(
-        match textures.get(&object.texture_name) {
-            Some(tex) => tex,
-            None => &missing_texture,
-        },
-        None,
-        object.rect,
-    );
-
-    match result {
-        Ok(_) => (),
-        Err(err) => println!("{}", err),
+use std::env;
+use std::hash::Hasher;
+use std::path::PathBuf;
+
+use crate::rect::FRect;
+
+pub fn overlaps(a: &FRect, b: &FRect) -> bool {
+    let a_xmax = a.x + a.width();
+    let a_ymax = a.y + a.height();
+
+    let b_xmax = b.x + b.width();
+    let b_ymax = b.y + b.height();
+
+    a_xmax > b.x && b_xmax > a.x && a_ymax > b.y && b_ymax > a.y
+}
+
+pub fn decrease_until_zero(value: i32, modifier: i32, min: i32) -> i32 {
+    if value - modifier > 0 {
+        value - modifier
+    } else {
+        min
     }
 }
 
-pub fn draw_anim_texture<'a>(
-    canvas: &mut Canvas<Window>,
-    textures: &HashMap<String, Texture<'a>>,
-    missing_texture: &Texture<'a>,
-    object: &GameObject,
-    anim_step: &i32,
-) {
-    let name_prefix = &object.texture_name;
-
-    let texture_name = if *anim_step == 1 {
-        let name_postfix = "_1";
-        format!("{name_prefix}{name_postfix}")
+fn app_dir(xdg_var: &str, xdg_default: &str) -> Option<PathBuf> {
+    let base = if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
     } else {
-        let name_postfix = "_2";
-        format!("{name_prefix}{name_postfix}")
+        env::var_os(xdg_var)
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(xdg_default)))
     };
 
-    let result = canvas.copy_f(
-        match textures.get(&texture_name) {
-            Some(tex) => tex,
-            None => &missing_texture,
-        },
-        None,
-        object.rect,
-    );
-
-    match result {
-        Ok(_) => (),
-        Err(err) => println!("{}", err),
-    }
+    base.map(|base| base.join("space-invaders-rust"))
 }
 
-pub fn draw_texture_nameless<'a>(canvas: &mut Canvas<Window>, texture: &Texture<'a>, rect: &FRect) {
-    let result = canvas.copy_f(texture, None, *rect);
+pub fn data_dir() -> Option<PathBuf> {
+    app_dir("XDG_DATA_HOME", ".local/share")
+}
 
-    match result {
-        Ok(_) => (),
-        Err(err) => println!("{}", err),
-    }
+pub fn config_dir() -> Option<PathBuf> {
+    app_dir("XDG_CONFIG_HOME", ".config")
 }
 
-pub fn draw_texture_nameless_rect<'a>(
-    canvas: &mut Canvas<Window>,
-    texture: &Texture<'a>,
-    rect: &Rect,
-) {
-    let result = canvas.copy(texture, None, *rect);
+// FNV-1a, used where a hash has to be identical across builds and platforms.
+// Integers are fed in as little-endian bytes and `usize` as 64 bits, so the
+// `Hash` impls it is given, including lengths and enum discriminants, hash
+// the same everywhere. Slices of integers are the exception: they hash as
+// raw native-endian bytes, so those have to be hashed one value at a time.
+pub struct Fnv1a(u64);
 
-    match result {
-        Ok(_) => (),
-        Err(err) => println!("{}", err),
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
     }
 }
 
-pub fn overlaps(a: &FRect, b: &FRect) -> bool {
-    let a_xmax = a.x + a.width();
-    let a_ymax = a.y + a.height();
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
 
-    let b_xmax = b.x + b.width();
-    let b_ymax = b.y + b.height();
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
 
-    a_xmax > b.x && b_xmax > a.x && a_ymax > b.y && b_ymax > a.y
-}
+    fn write_u16(&mut self, value: u16) {
+        self.write(&value.to_le_bytes());
+    }
 
-pub fn center_x(width: i32) -> i32 {
-    CANVAS_WIDTH - width - (width / 2)
-}
+    fn write_u32(&mut self, value: u32) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
 
-pub fn center_y(height: i32) -> i32 {
-    CANVAS_HEIGHT - height - (height / 2)
+    fn write_u128(&mut self, value: u128) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, value: usize) {
+        self.write_u64(value as u64);
+    }
 }
 
-pub fn decrease_until_zero(value: i32, modifier: i32, min: i32) -> i32 {
-    if value - modifier > 0 {
-        return value - modifier;
-    } else {
-        return min;
+#[cfg(test)]
+mod tests {
+    use std::hash::Hash;
+
+    use super::*;
+
+    fn hash_bytes(bytes: &[u8]) -> u64 {
+        let mut hasher = Fnv1a::default();
+        hasher.write(bytes);
+        hasher.finish()
+    }
+
+    #[test]
+    fn fnv1a_matches_the_reference_hash() {
+        assert_eq!(hash_bytes(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash_bytes(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(hash_bytes(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn fnv1a_hashes_integers_as_little_endian() {
+        let mut hasher = Fnv1a::default();
+        0x0102_0304_u32.hash(&mut hasher);
+        (-2_i16).hash(&mut hasher);
+        7_usize.hash(&mut hasher);
+
+        let mut bytes = vec![4, 3, 2, 1, 0xfe, 0xff];
+        bytes.extend_from_slice(&7_u64.to_le_bytes());
+
+        assert_eq!(hasher.finish(), hash_bytes(&bytes));
     }
 }
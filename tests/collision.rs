@@ -0,0 +1,176 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use space_invaders_rust::collision::{self, Broadphase};
+use space_invaders_rust::game::{Game, GameObject, PIXEL_SIZE};
+use space_invaders_rust::invader::Invader;
+use space_invaders_rust::layout::LayoutMode;
+use space_invaders_rust::player::Player;
+use space_invaders_rust::settings::{CollisionMode, Settings};
+
+const INVADER_NAMES: [&str; 3] = ["invader_texture1", "invader_texture2", "invader_texture3"];
+
+// Scatters invaders, shots and the player at random, including off the
+// edges of the field, so every kind of pair gets to collide.
+fn scenario(seed: u64) -> (Player, Game) {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let settings = Settings {
+        layout: if rng.gen() {
+            LayoutMode::Landscape
+        } else {
+            LayoutMode::Arcade
+        },
+        collision: if rng.gen() {
+            CollisionMode::Boxes
+        } else {
+            CollisionMode::Pixels
+        },
+        ..Settings::default()
+    };
+
+    let mut game = Game::new(seed, settings);
+    let mut player = Player::new(&game.layout);
+
+    let width = game.layout.width as f32;
+    let height = game.layout.height as f32;
+    let margin = 20.0 * PIXEL_SIZE as f32;
+    let position = |rng: &mut StdRng| {
+        (
+            rng.gen_range(-margin..width + margin),
+            rng.gen_range(-margin..height + margin),
+        )
+    };
+
+    game.invaders = (0..rng.gen_range(0..300))
+        .map(|i| {
+            let (x, y) = position(&mut rng);
+            let mut invader = Invader::new(
+                x,
+                y,
+                12 * PIXEL_SIZE,
+                8 * PIXEL_SIZE,
+                String::from(INVADER_NAMES[rng.gen_range(0..INVADER_NAMES.len())]),
+                i / 11,
+                i % 11,
+            );
+            invader.anim_step = rng.gen_range(1..=2);
+            invader
+        })
+        .collect();
+
+    player.bullets = (0..rng.gen_range(0..40))
+        .map(|_| {
+            let (x, y) = position(&mut rng);
+            GameObject::new(
+                x,
+                y,
+                PIXEL_SIZE as u32,
+                4 * PIXEL_SIZE as u32,
+                String::from("shot_texture"),
+            )
+        })
+        .collect();
+
+    game.invader_shots = (0..rng.gen_range(0..40))
+        .map(|_| {
+            let (x, y) = position(&mut rng);
+            GameObject::new(
+                x,
+                y,
+                3 * PIXEL_SIZE as u32,
+                7 * PIXEL_SIZE as u32,
+                String::from("invader_shot_texture"),
+            )
+        })
+        .collect();
+
+    let (x, y) = position(&mut rng);
+    player.game_object.rect.x = x;
+    player.game_object.rect.y = y;
+
+    game.ufo_active = rng.gen();
+    let (x, y) = position(&mut rng);
+    game.ufo.game_object.rect.x = x;
+    game.ufo.game_object.rect.y = y;
+
+    (player, game)
+}
+
+#[test]
+fn broadphase_matches_brute_force() {
+    for seed in 0..500 {
+        let (mut player, mut game) = scenario(seed);
+        let mut broadphase = Broadphase::new(&game.layout);
+        collision::update(&mut broadphase, &mut player, &mut game);
+
+        let (mut expected_player, mut expected_game) = scenario(seed);
+        collision::update_brute_force(&mut expected_player, &mut expected_game);
+
+        assert_eq!(
+            game.checksum(),
+            expected_game.checksum(),
+            "game differs for seed {}",
+            seed
+        );
+        assert_eq!(
+            player.game_object.is_destroyed, expected_player.game_object.is_destroyed,
+            "player differs for seed {}",
+            seed
+        );
+        assert_eq!(
+            player
+                .bullets
+                .iter()
+                .map(|bullet| bullet.is_destroyed)
+                .collect::<Vec<bool>>(),
+            expected_player
+                .bullets
+                .iter()
+                .map(|bullet| bullet.is_destroyed)
+                .collect::<Vec<bool>>(),
+            "bullets differ for seed {}",
+            seed
+        );
+        assert_eq!(game.ufo.points, expected_game.ufo.points);
+    }
+}
+
+// Runs the same scenario through both paths for several ticks in a row, so
+// barrier damage from one tick feeds into the next.
+#[test]
+fn broadphase_matches_brute_force_over_ticks() {
+    for seed in 0..50 {
+        let (mut player, mut game) = scenario(seed);
+        let (mut expected_player, mut expected_game) = scenario(seed);
+        let mut broadphase = Broadphase::new(&game.layout);
+
+        for _ in 0..30 {
+            collision::update(&mut broadphase, &mut player, &mut game);
+            collision::update_brute_force(&mut expected_player, &mut expected_game);
+
+            for bullet in player
+                .bullets
+                .iter_mut()
+                .chain(&mut expected_player.bullets)
+            {
+                bullet.rect.y -= 10.0;
+            }
+
+            for shot in game
+                .invader_shots
+                .iter_mut()
+                .chain(&mut expected_game.invader_shots)
+            {
+                shot.rect.y += 10.0;
+            }
+
+            assert_eq!(
+                game.checksum(),
+                expected_game.checksum(),
+                "game differs for seed {}",
+                seed
+            );
+        }
+    }
+}